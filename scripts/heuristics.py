@@ -2,8 +2,9 @@
 """
 Compare CDCL heuristics at the 3-SAT phase transition (l/n ≈ 4.27).
 
-Sweeps each heuristic axis independently (polarity, restart, deletion)
-and prints results as each configuration finishes.
+Sweeps each heuristic axis independently (branching, polarity,
+restart, deletion, backtracking, trail reuse) and prints results as each
+configuration finishes.
 
 Usage:
     uv run python scripts/heuristics.py [--n N] [--reps N] [--save FILE]
@@ -31,8 +32,55 @@ BIN = REPO / "target" / "release" / "random"
 PHASE_RATIO = 4.27  # l/n at the 3-SAT phase transition
 
 # Each section sweeps one axis while holding the others at the default.
-# Default config: polarity=phase-saving, restart=luby/100, deletion=lbd/6.
+# Default config: branching=evsids, polarity=phase-saving, restart=luby/100, deletion=lbd/6.
+# A section's optional "stat" names a counter from the solver's output whose
+# median is shown next to the timings.
 SECTIONS = [
+    {
+        "title": "branching",
+        "fixed": "polarity=phase-saving, restart=luby, deletion=lbd",
+        "configs": [
+            (
+                "evsids",
+                [
+                    "--branching",
+                    "evsids",
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
+            (
+                "vmtf",
+                [
+                    "--branching",
+                    "vmtf",
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
+            (
+                "chb",
+                [
+                    "--branching",
+                    "chb",
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
+        ],
+    },
     {
         "title": "polarity",
         "fixed": "restart=luby, deletion=lbd",
@@ -63,6 +111,17 @@ SECTIONS = [
                     "lbd",
                 ],
             ),
+            (
+                "target",
+                [
+                    "--polarity",
+                    "target",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
         ],
     },
     {
@@ -102,6 +161,17 @@ SECTIONS = [
                     "lbd",
                 ],
             ),
+            (
+                "glucose",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "glucose",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
         ],
     },
     {
@@ -141,6 +211,90 @@ SECTIONS = [
                     "activity",
                 ],
             ),
+            (
+                "tiered",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "tiered",
+                ],
+            ),
+        ],
+    },
+    {
+        "title": "backtracking",
+        "fixed": "polarity=phase-saving, restart=luby, deletion=lbd",
+        "configs": [
+            (
+                "non-chronological",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
+            (
+                "chronological",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                    "--chronological-backtracking",
+                ],
+            ),
+            (
+                "always-chronological",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                    "--chronological-backtracking",
+                    "--chronological-threshold",
+                    "0",
+                ],
+            ),
+        ],
+    },
+    {
+        "title": "trail reuse",
+        "fixed": "polarity=phase-saving, restart=luby, deletion=lbd",
+        "stat": "reused_levels",
+        "configs": [
+            (
+                "full-restart",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                ],
+            ),
+            (
+                "reuse-trail",
+                [
+                    "--polarity",
+                    "phase-saving",
+                    "--restart",
+                    "luby",
+                    "--deletion",
+                    "lbd",
+                    "--reuse-trail",
+                ],
+            ),
         ],
     },
     {
@@ -172,13 +326,15 @@ def run_config(args, n, reps):
     l = round(PHASE_RATIO * n)
     cmd = [str(BIN), "-n", str(n), "-l", str(l), "-r", str(reps), *args]
     result = subprocess.run(cmd, capture_output=True, text=True, check=True)
-    durations = []
+    runs = []
     for line in result.stdout.splitlines():
         try:
-            durations.append(int(json.loads(line)["duration_ms"]))
-        except (json.JSONDecodeError, KeyError):
+            run = json.loads(line)
+        except json.JSONDecodeError:
             continue
-    return durations
+        if "duration_ms" in run:
+            runs.append(run)
+    return runs
 
 
 def fmt_cell(samples):
@@ -193,68 +349,87 @@ def section_header(section, ns, reps):
     label_w = max(len(lb) for lb, _ in section["configs"])
     single_n = len(ns) == 1
 
+    stat = section.get("stat")
+
     if single_n:
         print(f"\n{title}  ({fixed})  n={ns[0]}, {reps} reps")
-        print(f"  {'':>{label_w}}   median     p90     max")
+        header = f"  {'':>{label_w}}   median     p90     max"
+        if stat:
+            header += f"  {stat} (median)"
+        print(header)
     else:
         col_w = 14
         print(f"\n{title}  ({fixed})  {reps} reps")
         print(f"  {'':>{label_w}}" + "".join(f"  {'n='+str(n):^{col_w}}" for n in ns))
         print(f"  {'':>{label_w}}" + "".join(f"  {'med    p90':^{col_w}}" for _ in ns))
+        if stat:
+            print(f"  {'':>{label_w}}  then the median {stat} for each n")
 
     return label_w
 
 
-def print_row(label, label_w, samples_by_n, ns):
+def print_row(label, label_w, samples_by_n, ns, stats_by_n=None):
     single_n = len(ns) == 1
     if single_n:
         med, p90, mx = fmt_cell(samples_by_n[ns[0]])
-        print(f"  {label:>{label_w}}   {med:>5}ms  {p90:>5}ms  {mx:>5}ms")
+        row = f"  {label:>{label_w}}   {med:>5}ms  {p90:>5}ms  {mx:>5}ms"
     else:
-        col_w = 14
         parts = [f"  {label:>{label_w}}"]
         for n in ns:
             med, p90, _ = fmt_cell(samples_by_n[n])
             parts.append(f"  {med:>4}ms {p90:>4}ms  ")
-        print("".join(parts))
+        row = "".join(parts)
+    if stats_by_n:
+        row += "".join(
+            f"  {statistics.median(stats_by_n[n]):>8g}" for n in ns if stats_by_n.get(n)
+        )
+    print(row)
 
 
 def sweep_and_display(ns, reps, save_fh=None):
     for section in SECTIONS:
+        stat = section.get("stat")
         label_w = section_header(section, ns, reps)
         for label, args in section["configs"]:
             samples_by_n = {}
+            stats_by_n = {}
             for n in ns:
-                durations = run_config(args, n, reps)
-                samples_by_n[n] = durations
+                runs = run_config(args, n, reps)
+                samples_by_n[n] = [int(run["duration_ms"]) for run in runs]
+                if stat:
+                    stats_by_n[n] = [int(run[stat]) for run in runs if stat in run]
                 if save_fh:
-                    for d in durations:
-                        save_fh.write(
-                            json.dumps(
-                                {
-                                    "section": section["title"],
-                                    "label": label,
-                                    "n": n,
-                                    "duration_ms": d,
-                                }
-                            )
-                            + "\n"
-                        )
+                    for run in runs:
+                        record = {
+                            "section": section["title"],
+                            "label": label,
+                            "n": n,
+                            "duration_ms": int(run["duration_ms"]),
+                        }
+                        if stat and stat in run:
+                            record[stat] = int(run[stat])
+                        save_fh.write(json.dumps(record) + "\n")
                     save_fh.flush()
-            print_row(label, label_w, samples_by_n, ns)
+            print_row(label, label_w, samples_by_n, ns, stats_by_n)
             sys.stdout.flush()
 
 
 def load_and_display(path):
     data = defaultdict(lambda: defaultdict(list))
+    stats = defaultdict(lambda: defaultdict(list))
+    stat_names = {section["title"]: section.get("stat") for section in SECTIONS}
     ns_seen = set()
     for line in path.read_text().splitlines():
         try:
             d = json.loads(line)
-            data[(d["section"], d["label"])][int(d["n"])].append(int(d["duration_ms"]))
+            key = (d["section"], d["label"])
+            data[key][int(d["n"])].append(int(d["duration_ms"]))
             ns_seen.add(int(d["n"]))
         except (json.JSONDecodeError, KeyError):
             continue
+        stat = stat_names.get(d["section"])
+        if stat and stat in d:
+            stats[key][int(d["n"])].append(int(d[stat]))
 
     ns = sorted(ns_seen)
     reps = min(len(v) for by_n in data.values() for v in by_n.values())
@@ -264,7 +439,8 @@ def load_and_display(path):
         for label, _ in section["configs"]:
             samples_by_n = data.get((section["title"], label), {})
             if samples_by_n:
-                print_row(label, label_w, samples_by_n, ns)
+                stats_by_n = stats.get((section["title"], label), {})
+                print_row(label, label_w, samples_by_n, ns, stats_by_n)
 
 
 def main():
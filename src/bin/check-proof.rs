@@ -0,0 +1,89 @@
+use satsolver::parser;
+use satsolver::proof_check::{self, Verdict};
+
+use clap::Parser;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Check a DRAT or LRAT refutation of a CNF formula", long_about = None)]
+struct Args {
+    /// Proof format; `auto` picks LRAT for `.lrat` files and DRAT otherwise.
+    /// Binary DRAT is detected automatically.
+    #[arg(short, long, default_value = "auto")]
+    format: FormatOption,
+
+    /// CNF formula the proof refutes
+    cnf: String,
+
+    /// Proof file
+    proof: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum FormatOption {
+    Auto,
+    Drat,
+    Lrat,
+}
+
+fn open(path: &str) -> BufReader<File> {
+    match File::open(path) {
+        Ok(file) => BufReader::new(file),
+        Err(e) => {
+            eprintln!("Failed to open file '{path}': {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let args = Args::parse();
+
+    let cnf = match parser::parse_dimacs(open(&args.cnf)) {
+        Ok(cnf) => cnf,
+        Err(e) => {
+            eprintln!("Failed to parse '{}': {e}", args.cnf);
+            std::process::exit(1);
+        }
+    };
+
+    let format = match args.format {
+        FormatOption::Auto
+            if Path::new(&args.proof).extension().and_then(|s| s.to_str()) == Some("lrat") =>
+        {
+            FormatOption::Lrat
+        }
+        FormatOption::Auto => FormatOption::Drat,
+        other => other,
+    };
+
+    let start_time = Instant::now();
+    let verdict = match format {
+        FormatOption::Lrat => proof_check::check_lrat(&cnf, open(&args.proof)),
+        _ => proof_check::check_drat(&cnf, open(&args.proof)),
+    };
+    let duration = start_time.elapsed();
+
+    match verdict {
+        Ok(Verdict::Verified) => {
+            println!(
+                "\x1b[32mVERIFIED: {} in {:.3}s\x1b[0m",
+                args.proof,
+                duration.as_secs_f64()
+            );
+        }
+        Ok(verdict) => {
+            println!("\x1b[31m{verdict}\x1b[0m");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to parse '{}': {e}", args.proof);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,16 +1,25 @@
 use std::io::Write;
 
 use clap::Parser;
+use rand::prelude::*;
 use satsolver::config::*;
 use satsolver::random;
 use satsolver::solve_cdcl;
 use serde_json::json;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum BranchingOption {
+    Evsids,
+    Vmtf,
+    Chb,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 enum PolarityOption {
     AlwaysFalse,
     AlwaysTrue,
     PhaseSaving,
+    Target,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -18,6 +27,7 @@ enum RestartOption {
     None,
     Luby,
     Geometric,
+    Glucose,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -25,6 +35,13 @@ enum DeletionOption {
     None,
     Lbd,
     Activity,
+    Tiered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum MinimizationOption {
+    None,
+    Recursive,
 }
 
 #[derive(Parser, Debug)]
@@ -44,6 +61,12 @@ struct Args {
     #[arg(short, long, default_value_t = 1)]
     repetitions: usize,
 
+    #[arg(long, default_value = "evsids")]
+    branching: BranchingOption,
+
+    #[arg(long, default_value_t = 0.95)]
+    branching_decay: f64,
+
     #[arg(long, default_value = "phase-saving")]
     polarity: PolarityOption,
 
@@ -59,24 +82,81 @@ struct Args {
     #[arg(long, default_value_t = 1.5)]
     restart_factor: f64,
 
+    #[arg(long, default_value_t = 1.25)]
+    restart_margin: f64,
+
+    #[arg(long, default_value_t = 1.4)]
+    restart_blocking: f64,
+
     #[arg(long, default_value = "lbd")]
     deletion: DeletionOption,
 
     #[arg(long, default_value_t = 6)]
     deletion_max_lbd: u32,
 
+    #[arg(long, default_value_t = 2)]
+    deletion_core_lbd: u32,
+
     #[arg(long, default_value_t = 0.5)]
     deletion_fraction: f64,
+
+    #[arg(long, default_value = "recursive")]
+    minimization: MinimizationOption,
+
+    #[arg(long, default_value_t = 4)]
+    subsumption_interval: u32,
+
+    #[arg(long, default_value_t = 2_000)]
+    vivification_budget: u32,
+
+    #[arg(long, default_value_t = 4)]
+    vivification_interval: u32,
+
+    #[arg(long, default_value_t = 20_000)]
+    probe_budget: u32,
+
+    #[arg(long, default_value_t = 4)]
+    probe_interval: u32,
+
+    /// Seed for the formulas and the solver
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    #[arg(long, default_value_t = 0.0)]
+    random_var_frequency: f64,
+
+    #[arg(long, default_value_t = 0.0)]
+    random_polarity_frequency: f64,
+
+    #[arg(long)]
+    initial_shuffle: bool,
+
+    #[arg(long)]
+    chronological_backtracking: bool,
+
+    #[arg(long, default_value_t = 100)]
+    chronological_threshold: u32,
+
+    #[arg(long)]
+    reuse_trail: bool,
 }
 
 fn main() {
     let args = Args::parse();
 
     let config = SolverConfig {
+        branching: match args.branching {
+            BranchingOption::Evsids => BranchingHeuristic::Evsids {
+                decay: args.branching_decay,
+            },
+            BranchingOption::Vmtf => BranchingHeuristic::Vmtf,
+            BranchingOption::Chb => BranchingHeuristic::Chb,
+        },
         polarity: match args.polarity {
             PolarityOption::AlwaysFalse => PolarityHeuristic::AlwaysFalse,
             PolarityOption::AlwaysTrue => PolarityHeuristic::AlwaysTrue,
             PolarityOption::PhaseSaving => PolarityHeuristic::PhaseSaving,
+            PolarityOption::Target => PolarityHeuristic::Target,
         },
         restart: match args.restart {
             RestartOption::None => RestartStrategy::None,
@@ -87,6 +167,10 @@ fn main() {
                 initial: args.restart_initial,
                 factor: args.restart_factor,
             },
+            RestartOption::Glucose => RestartStrategy::Glucose {
+                margin: args.restart_margin,
+                blocking: args.restart_blocking,
+            },
         },
         deletion: match args.deletion {
             DeletionOption::None => DeletionStrategy::None,
@@ -96,29 +180,65 @@ fn main() {
             DeletionOption::Activity => DeletionStrategy::Activity {
                 fraction: args.deletion_fraction,
             },
+            DeletionOption::Tiered => DeletionStrategy::Tiered {
+                core_lbd: args.deletion_core_lbd,
+                tier2_lbd: args.deletion_max_lbd,
+                fraction: args.deletion_fraction,
+            },
         },
+        minimization: match args.minimization {
+            MinimizationOption::None => ClauseMinimization::None,
+            MinimizationOption::Recursive => ClauseMinimization::Recursive,
+        },
+        preprocess: false,
+        proof: false,
+        subsumption_interval: args.subsumption_interval,
+        vivification_budget: args.vivification_budget,
+        vivification_interval: args.vivification_interval,
+        probe_budget: args.probe_budget,
+        probe_interval: args.probe_interval,
+        seed: args.seed,
+        random_var_frequency: args.random_var_frequency,
+        random_polarity_frequency: args.random_polarity_frequency,
+        initial_shuffle: args.initial_shuffle,
+        chronological_backtracking: args.chronological_backtracking,
+        chronological_threshold: args.chronological_threshold,
+        reuse_trail: args.reuse_trail,
     };
 
     let config_label = format!(
-        "{}/{}/{}",
+        "{}/{}/{}/{}",
+        match args.branching {
+            BranchingOption::Evsids => format!("evsids({})", args.branching_decay),
+            BranchingOption::Vmtf => "vmtf".to_string(),
+            BranchingOption::Chb => "chb".to_string(),
+        },
         match args.polarity {
             PolarityOption::AlwaysFalse => "always-false",
             PolarityOption::AlwaysTrue => "always-true",
             PolarityOption::PhaseSaving => "phase-saving",
+            PolarityOption::Target => "target",
         },
         match args.restart {
             RestartOption::None => "none".to_string(),
             RestartOption::Luby => format!("luby({})", args.restart_unit),
             RestartOption::Geometric =>
                 format!("geo({},{})", args.restart_initial, args.restart_factor),
+            RestartOption::Glucose =>
+                format!("glucose({},{})", args.restart_margin, args.restart_blocking),
         },
         match args.deletion {
             DeletionOption::None => "none".to_string(),
             DeletionOption::Lbd => format!("lbd({})", args.deletion_max_lbd),
             DeletionOption::Activity => format!("act({})", args.deletion_fraction),
+            DeletionOption::Tiered => format!(
+                "tiered({},{},{})",
+                args.deletion_core_lbd, args.deletion_max_lbd, args.deletion_fraction
+            ),
         },
     );
 
+    let mut rng = StdRng::seed_from_u64(args.seed);
     for num_variables in &args.num_variables {
         for num_clauses in &args.num_clauses {
             let mut successful = 0;
@@ -127,9 +247,10 @@ fn main() {
                     *num_variables,
                     args.num_variables_per_clause,
                     *num_clauses,
+                    &mut rng,
                 );
                 let start = std::time::Instant::now();
-                let answer = solve_cdcl::solve_cdcl(&cnf, &config);
+                let (answer, stats) = solve_cdcl::solve_cdcl_with_stats(&cnf, &config);
                 let duration = start.elapsed();
 
                 let result = json!({
@@ -139,6 +260,7 @@ fn main() {
                     "l": num_clauses,
                     "sat": answer.is_satisfiable(),
                     "duration_ms": duration.as_millis(),
+                    "reused_levels": stats.reused_levels,
                 });
                 println!("{result}");
                 std::io::stdout().flush().unwrap();
@@ -21,6 +21,14 @@ struct Args {
     #[arg(short, long, default_value_t = 3)]
     depth: usize,
 
+    /// Decision variable heuristic (cdcl/cnc only)
+    #[arg(long, default_value = "evsids")]
+    branching: BranchingOption,
+
+    /// Activity decay factor for EVSIDS branching
+    #[arg(long, default_value_t = 0.95)]
+    branching_decay: f64,
+
     /// Variable polarity heuristic (cdcl/cnc only)
     #[arg(long, default_value = "phase-saving")]
     polarity: PolarityOption,
@@ -41,18 +49,92 @@ struct Args {
     #[arg(long, default_value_t = 1.5)]
     restart_factor: f64,
 
+    /// Ratio of recent to overall average LBD that triggers glucose restarts
+    #[arg(long, default_value_t = 1.25)]
+    restart_margin: f64,
+
+    /// Ratio of trail size to its recent average that blocks glucose restarts
+    #[arg(long, default_value_t = 1.4)]
+    restart_blocking: f64,
+
     /// Clause deletion strategy (cdcl/cnc only)
     #[arg(long, default_value = "lbd")]
     deletion: DeletionOption,
 
-    /// Maximum LBD to retain when using --deletion=lbd
+    /// Maximum LBD to retain when using --deletion=lbd, or of tier-2 clauses
+    /// when using --deletion=tiered
     #[arg(long, default_value_t = 6)]
     deletion_max_lbd: u32,
 
-    /// Fraction of learned clauses to evict when using --deletion=activity
+    /// Maximum LBD of core clauses when using --deletion=tiered
+    #[arg(long, default_value_t = 2)]
+    deletion_core_lbd: u32,
+
+    /// Fraction of learned clauses to evict when using --deletion=activity,
+    /// or of local clauses when using --deletion=tiered
     #[arg(long, default_value_t = 0.5)]
     deletion_fraction: f64,
 
+    /// Learned clause minimization (cdcl/cnc only)
+    #[arg(long, default_value = "recursive")]
+    minimization: MinimizationOption,
+
+    /// Simplify the formula before search (cdcl/cnc only)
+    #[arg(long)]
+    preprocess: bool,
+
+    /// Restarts between rounds of learned clause subsumption, 0 to disable (cdcl/cnc only)
+    #[arg(long, default_value_t = 4)]
+    subsumption_interval: u32,
+
+    /// Literals vivification may propagate in each round, 0 to disable (cdcl/cnc only)
+    #[arg(long, default_value_t = 2_000)]
+    vivification_budget: u32,
+
+    /// Restarts between rounds of vivification, 0 to disable (cdcl/cnc only)
+    #[arg(long, default_value_t = 4)]
+    vivification_interval: u32,
+
+    /// Literals failed literal probing may propagate in each round, 0 to disable (cdcl/cnc only)
+    #[arg(long, default_value_t = 20_000)]
+    probe_budget: u32,
+
+    /// Restarts between rounds of failed literal probing, 0 to disable (cdcl/cnc only)
+    #[arg(long, default_value_t = 4)]
+    probe_interval: u32,
+
+    /// Seed for random decisions and rephasing (cdcl/cnc only)
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Probability of deciding on a random variable (cdcl/cnc only)
+    #[arg(long, default_value_t = 0.0)]
+    random_var_frequency: f64,
+
+    /// Probability of deciding on a random polarity (cdcl/cnc only)
+    #[arg(long, default_value_t = 0.0)]
+    random_polarity_frequency: f64,
+
+    /// Start from a random variable order (cdcl/cnc only)
+    #[arg(long)]
+    initial_shuffle: bool,
+
+    /// Backtrack a single level after conflicts that would jump further (cdcl/cnc only)
+    #[arg(long)]
+    chronological_backtracking: bool,
+
+    /// Levels a conflict must jump back to trigger chronological backtracking
+    #[arg(long, default_value_t = 100)]
+    chronological_threshold: u32,
+
+    /// Keep the decision levels a restart would rebuild, on restarts without inprocessing (cdcl/cnc only)
+    #[arg(long)]
+    reuse_trail: bool,
+
+    /// Format of the UNSAT proof written to the output directory
+    #[arg(long, default_value = "drat")]
+    proof_format: ProofFormatOption,
+
     /// Input CNF files to solve. Use '-' for stdin.
     /// Multiple files allowed only when no output files are specified.
     file: Vec<String>,
@@ -72,11 +154,19 @@ enum SolverOption {
     Basic,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum BranchingOption {
+    Evsids,
+    Vmtf,
+    Chb,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 enum PolarityOption {
     AlwaysFalse,
     AlwaysTrue,
     PhaseSaving,
+    Target,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -84,6 +174,7 @@ enum RestartOption {
     None,
     Luby,
     Geometric,
+    Glucose,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -91,6 +182,20 @@ enum DeletionOption {
     None,
     Lbd,
     Activity,
+    Tiered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum MinimizationOption {
+    None,
+    Recursive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ProofFormatOption {
+    Drat,
+    BinaryDrat,
+    Lrat,
 }
 
 fn generate_output_filename(input_file: &str, output_dir: &str, extension: &str) -> PathBuf {
@@ -121,10 +226,18 @@ fn main() {
     }
 
     let config = SolverConfig {
+        branching: match args.branching {
+            BranchingOption::Evsids => BranchingHeuristic::Evsids {
+                decay: args.branching_decay,
+            },
+            BranchingOption::Vmtf => BranchingHeuristic::Vmtf,
+            BranchingOption::Chb => BranchingHeuristic::Chb,
+        },
         polarity: match args.polarity {
             PolarityOption::AlwaysFalse => PolarityHeuristic::AlwaysFalse,
             PolarityOption::AlwaysTrue => PolarityHeuristic::AlwaysTrue,
             PolarityOption::PhaseSaving => PolarityHeuristic::PhaseSaving,
+            PolarityOption::Target => PolarityHeuristic::Target,
         },
         restart: match args.restart {
             RestartOption::None => RestartStrategy::None,
@@ -135,6 +248,10 @@ fn main() {
                 initial: args.restart_initial,
                 factor: args.restart_factor,
             },
+            RestartOption::Glucose => RestartStrategy::Glucose {
+                margin: args.restart_margin,
+                blocking: args.restart_blocking,
+            },
         },
         deletion: match args.deletion {
             DeletionOption::None => DeletionStrategy::None,
@@ -144,7 +261,31 @@ fn main() {
             DeletionOption::Activity => DeletionStrategy::Activity {
                 fraction: args.deletion_fraction,
             },
+            DeletionOption::Tiered => DeletionStrategy::Tiered {
+                core_lbd: args.deletion_core_lbd,
+                tier2_lbd: args.deletion_max_lbd,
+                fraction: args.deletion_fraction,
+            },
         },
+        minimization: match args.minimization {
+            MinimizationOption::None => ClauseMinimization::None,
+            MinimizationOption::Recursive => ClauseMinimization::Recursive,
+        },
+        preprocess: args.preprocess,
+        // Proofs are only written to the output directory.
+        proof: args.output_dir.is_some(),
+        subsumption_interval: args.subsumption_interval,
+        vivification_budget: args.vivification_budget,
+        vivification_interval: args.vivification_interval,
+        probe_budget: args.probe_budget,
+        probe_interval: args.probe_interval,
+        seed: args.seed,
+        random_var_frequency: args.random_var_frequency,
+        random_polarity_frequency: args.random_polarity_frequency,
+        initial_shuffle: args.initial_shuffle,
+        chronological_backtracking: args.chronological_backtracking,
+        chronological_threshold: args.chronological_threshold,
+        reuse_trail: args.reuse_trail,
     };
 
     let mut total_duration = Duration::from_secs(0);
@@ -184,11 +325,17 @@ fn main() {
             )
             .unwrap();
             if let Some(proof) = answer.unsat_proof() {
-                let drat_path = generate_output_filename(&file, output_dir, "drat");
-                parser::output_drat(
-                    &mut BufWriter::new(File::create(&drat_path).unwrap()),
-                    &proof,
-                )
+                let extension = match args.proof_format {
+                    ProofFormatOption::Drat | ProofFormatOption::BinaryDrat => "drat",
+                    ProofFormatOption::Lrat => "lrat",
+                };
+                let proof_path = generate_output_filename(&file, output_dir, extension);
+                let mut writer = BufWriter::new(File::create(&proof_path).unwrap());
+                match args.proof_format {
+                    ProofFormatOption::Drat => parser::output_drat(&mut writer, proof),
+                    ProofFormatOption::BinaryDrat => parser::output_binary_drat(&mut writer, proof),
+                    ProofFormatOption::Lrat => parser::output_lrat(&mut writer, proof),
+                }
                 .unwrap();
             }
         }
@@ -0,0 +1,500 @@
+// Decision variable ordering. Each heuristic keeps every unassigned variable
+// among its candidates, and is told when variables are assigned, unassigned,
+// propagated, or take part in a conflict.
+
+use rand::prelude::*;
+
+use crate::config::BranchingHeuristic;
+use crate::formula::*;
+use crate::solver_state::Assignment;
+
+// Indexed max-heap for VSIDS decision ordering. Supports O(log n) insert,
+// remove, and priority-increase (sift_up only — scores only change while
+// their variable is out of the heap, or increase).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActivityHeap {
+    heap: Vec<usize>,        // heap positions → 0-based var indices
+    pos: Vec<Option<usize>>, // 0-based var index → heap position (None if absent)
+}
+
+impl ActivityHeap {
+    pub fn new(n: usize) -> Self {
+        Self {
+            heap: (0..n).collect(),
+            pos: (0..n).map(Some).collect(),
+        }
+    }
+
+    pub fn peek(&self) -> Option<usize> {
+        self.heap.first().copied()
+    }
+
+    fn sift_up(&mut self, mut p: usize, activity: &[f64]) {
+        while p > 0 {
+            let parent = (p - 1) / 2;
+            if activity[self.heap[p]] > activity[self.heap[parent]] {
+                self.pos[self.heap[p]] = Some(parent);
+                self.pos[self.heap[parent]] = Some(p);
+                self.heap.swap(p, parent);
+                p = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn sift_down(&mut self, mut p: usize, activity: &[f64]) {
+        let n = self.heap.len();
+        loop {
+            let left = 2 * p + 1;
+            let right = 2 * p + 2;
+            let mut largest = p;
+            if left < n && activity[self.heap[left]] > activity[self.heap[largest]] {
+                largest = left;
+            }
+            if right < n && activity[self.heap[right]] > activity[self.heap[largest]] {
+                largest = right;
+            }
+            if largest == p {
+                break;
+            }
+            self.pos[self.heap[p]] = Some(largest);
+            self.pos[self.heap[largest]] = Some(p);
+            self.heap.swap(p, largest);
+            p = largest;
+        }
+    }
+
+    pub fn insert(&mut self, var_idx: usize, activity: &[f64]) {
+        if self.pos[var_idx].is_none() {
+            let p = self.heap.len();
+            self.heap.push(var_idx);
+            self.pos[var_idx] = Some(p);
+            self.sift_up(p, activity);
+        }
+    }
+
+    pub fn remove(&mut self, var_idx: usize, activity: &[f64]) {
+        let Some(p) = self.pos[var_idx].take() else {
+            return;
+        };
+        let last = self.heap.len() - 1;
+        if p != last {
+            let moved = self.heap[last];
+            self.heap[p] = moved;
+            self.pos[moved] = Some(p);
+            self.heap.pop();
+            self.sift_up(p, activity);
+            if self.pos[moved] == Some(p) {
+                self.sift_down(p, activity);
+            }
+        } else {
+            self.heap.pop();
+        }
+    }
+
+    pub fn update(&mut self, var_idx: usize, activity: &[f64]) {
+        if let Some(p) = self.pos[var_idx] {
+            self.sift_up(p, activity);
+        }
+    }
+
+    // Restore the heap property after arbitrary changes to `activity`.
+    pub fn rebuild(&mut self, activity: &[f64]) {
+        for p in (0..self.heap.len() / 2).rev() {
+            self.sift_down(p, activity);
+        }
+    }
+
+    // The new variable must already have an entry in `activity`.
+    pub fn add_var(&mut self, activity: &[f64]) {
+        let var_idx = self.pos.len();
+        self.pos.push(None);
+        self.insert(var_idx, activity);
+    }
+}
+
+// CHB step size: starts at STEP_INITIAL and shrinks by STEP_DECAY every
+// conflict, down to STEP_MIN.
+const STEP_INITIAL: f64 = 0.4;
+const STEP_DECAY: f64 = 1e-6;
+const STEP_MIN: f64 = 0.06;
+// CHB reward multipliers for propagations that do and do not end in a conflict.
+const CONFLICT_MULTIPLIER: f64 = 1.0;
+const NO_CONFLICT_MULTIPLIER: f64 = 0.9;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Branching {
+    // Activities grow by `var_inc`, which is divided by `decay` after each
+    // conflict, so recent conflicts count for more.
+    Evsids {
+        decay: f64,
+        activity: Vec<f64>,
+        var_inc: f64,
+        heap: ActivityHeap,
+    },
+    // A queue of variables, doubly linked through `prev` and `next`, with the
+    // most recently bumped last. Stamps increase along the queue. Every
+    // variable after `search` is assigned.
+    Vmtf {
+        prev: Vec<Option<usize>>,
+        next: Vec<Option<usize>>,
+        stamp: Vec<u64>,
+        first: Option<usize>,
+        last: Option<usize>,
+        search: Option<usize>,
+        counter: u64,
+    },
+    // Scores are moving averages of rewards for being assigned, higher the
+    // more recently the variable took part in a conflict.
+    Chb {
+        q: Vec<f64>,
+        last_conflict: Vec<u32>,
+        step: f64,
+        heap: ActivityHeap,
+    },
+}
+
+impl Branching {
+    /// Every variable starts out unassigned.
+    pub fn new(heuristic: BranchingHeuristic, num_vars: usize) -> Self {
+        match heuristic {
+            BranchingHeuristic::Evsids { decay } => Branching::Evsids {
+                decay,
+                activity: vec![0.0; num_vars],
+                var_inc: 1.0,
+                heap: ActivityHeap::new(num_vars),
+            },
+            BranchingHeuristic::Vmtf => {
+                let mut vmtf = Branching::Vmtf {
+                    prev: vec![],
+                    next: vec![],
+                    stamp: vec![],
+                    first: None,
+                    last: None,
+                    search: None,
+                    counter: 0,
+                };
+                for _ in 0..num_vars {
+                    vmtf.add_var();
+                }
+                vmtf
+            }
+            BranchingHeuristic::Chb => Branching::Chb {
+                q: vec![0.0; num_vars],
+                last_conflict: vec![0; num_vars],
+                step: STEP_INITIAL,
+                heap: ActivityHeap::new(num_vars),
+            },
+        }
+    }
+
+    /// Add an unassigned variable.
+    pub fn add_var(&mut self) {
+        match self {
+            Branching::Evsids { activity, heap, .. } => {
+                activity.push(0.0);
+                heap.add_var(activity);
+            }
+            Branching::Vmtf {
+                prev,
+                next,
+                stamp,
+                first,
+                last,
+                search,
+                counter,
+            } => {
+                let var = prev.len();
+                *counter += 1;
+                prev.push(*last);
+                next.push(None);
+                stamp.push(*counter);
+                match *last {
+                    Some(l) => next[l] = Some(var),
+                    None => *first = Some(var),
+                }
+                *last = Some(var);
+                *search = Some(var);
+            }
+            Branching::Chb {
+                q,
+                last_conflict,
+                heap,
+                ..
+            } => {
+                q.push(0.0);
+                last_conflict.push(0);
+                heap.add_var(q);
+            }
+        }
+    }
+
+    /// Break ties between variables at random rather than by index. Must be
+    /// called before any variable is assigned.
+    pub fn shuffle(&mut self, rng: &mut StdRng) {
+        match self {
+            Branching::Evsids { activity, heap, .. } => {
+                // Small enough for the first bump to outweigh.
+                for a in activity.iter_mut() {
+                    *a = rng.random::<f64>() * 1e-3;
+                }
+                heap.rebuild(activity);
+            }
+            Branching::Chb { q, heap, .. } => {
+                for score in q.iter_mut() {
+                    *score = rng.random::<f64>() * 1e-3;
+                }
+                heap.rebuild(q);
+            }
+            Branching::Vmtf {
+                prev,
+                next,
+                stamp,
+                first,
+                last,
+                search,
+                counter,
+            } => {
+                let mut order = (0..prev.len()).collect::<Vec<_>>();
+                order.shuffle(rng);
+                *counter = 0;
+                *first = None;
+                *last = None;
+                for var in order {
+                    *counter += 1;
+                    prev[var] = *last;
+                    next[var] = None;
+                    stamp[var] = *counter;
+                    match *last {
+                        Some(l) => next[l] = Some(var),
+                        None => *first = Some(var),
+                    }
+                    *last = Some(var);
+                }
+                *search = *last;
+            }
+        }
+    }
+
+    pub fn assigned(&mut self, var_idx: usize) {
+        match self {
+            Branching::Evsids { activity, heap, .. } => heap.remove(var_idx, activity),
+            Branching::Chb { q, heap, .. } => heap.remove(var_idx, q),
+            // Assigned variables are skipped over when searching the queue.
+            Branching::Vmtf { .. } => {}
+        }
+    }
+
+    pub fn unassigned(&mut self, var_idx: usize) {
+        match self {
+            Branching::Evsids { activity, heap, .. } => heap.insert(var_idx, activity),
+            Branching::Chb { q, heap, .. } => heap.insert(var_idx, q),
+            Branching::Vmtf { stamp, search, .. } => {
+                if search.is_none_or(|s| stamp[var_idx] > stamp[s]) {
+                    *search = Some(var_idx);
+                }
+            }
+        }
+    }
+
+    /// Reward the variables of `literals`, which took part in conflict
+    /// number `conflict_count`.
+    pub fn bump(&mut self, literals: &[PackedLit], assignment: &Assignment, conflict_count: u32) {
+        match self {
+            Branching::Evsids {
+                decay,
+                activity,
+                var_inc,
+                heap,
+            } => {
+                for lit in literals {
+                    let i = lit.var_index();
+                    activity[i] += *var_inc;
+                    heap.update(i, activity);
+                }
+                *var_inc /= *decay;
+                if *var_inc > 1e100 {
+                    for a in activity.iter_mut() {
+                        *a /= 1e100;
+                    }
+                    *var_inc /= 1e100;
+                    // Relative order is preserved by uniform rescaling, so the heap
+                    // remains valid — no rebuild needed.
+                }
+            }
+            Branching::Vmtf {
+                prev,
+                next,
+                stamp,
+                first,
+                last,
+                search,
+                counter,
+            } => {
+                // Move to the end in queue order, so bumped variables keep
+                // their relative order.
+                let mut vars = literals
+                    .iter()
+                    .map(|lit| lit.var_index())
+                    .collect::<Vec<_>>();
+                vars.sort_unstable_by_key(|&var| stamp[var]);
+                vars.dedup();
+                for var in vars {
+                    if *last != Some(var) {
+                        match prev[var] {
+                            Some(p) => next[p] = next[var],
+                            None => *first = next[var],
+                        }
+                        if let Some(n) = next[var] {
+                            prev[n] = prev[var];
+                        }
+                        if *search == Some(var) {
+                            *search = prev[var].or(next[var]);
+                        }
+                        prev[var] = *last;
+                        next[var] = None;
+                        if let Some(l) = *last {
+                            next[l] = Some(var);
+                        }
+                        *last = Some(var);
+                    }
+                    *counter += 1;
+                    stamp[var] = *counter;
+                    if assignment.value(PackedLit::new(var, Val::True)).is_none() {
+                        *search = Some(var);
+                    }
+                }
+            }
+            Branching::Chb {
+                last_conflict,
+                step,
+                ..
+            } => {
+                for lit in literals {
+                    last_conflict[lit.var_index()] = conflict_count;
+                }
+                *step = (*step - STEP_DECAY).max(STEP_MIN);
+            }
+        }
+    }
+
+    /// Reward the variables of `literals`, just assigned and propagated,
+    /// for how recently they took part in a conflict, and more if
+    /// propagation ended in one.
+    pub fn propagated(
+        &mut self,
+        literals: impl IntoIterator<Item = PackedLit>,
+        conflict: bool,
+        conflict_count: u32,
+    ) {
+        let Branching::Chb {
+            q,
+            last_conflict,
+            step,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let multiplier = if conflict {
+            CONFLICT_MULTIPLIER
+        } else {
+            NO_CONFLICT_MULTIPLIER
+        };
+        for lit in literals {
+            let i = lit.var_index();
+            let reward = multiplier / (conflict_count - last_conflict[i] + 1) as f64;
+            // Assigned variables are out of the heap, so the score may drop.
+            q[i] = (1.0 - *step) * q[i] + *step * reward;
+        }
+    }
+
+    /// Whether the heuristic ranks variable `a` above variable `b`.
+    pub fn prefers(&self, a: usize, b: usize) -> bool {
+        match self {
+            Branching::Evsids { activity, .. } => activity[a] > activity[b],
+            Branching::Chb { q, .. } => q[a] > q[b],
+            Branching::Vmtf { stamp, .. } => stamp[a] > stamp[b],
+        }
+    }
+
+    /// The unassigned variable to decide on next, if any.
+    pub fn next(&mut self, assignment: &Assignment) -> Option<Var> {
+        let var_idx = match self {
+            Branching::Evsids { heap, .. } | Branching::Chb { heap, .. } => heap.peek(),
+            Branching::Vmtf {
+                prev,
+                first,
+                search,
+                ..
+            } => {
+                let mut var = (*search)?;
+                while assignment.value(PackedLit::new(var, Val::True)).is_some() {
+                    match prev[var] {
+                        Some(p) => var = p,
+                        None => {
+                            *search = *first;
+                            return None;
+                        }
+                    }
+                }
+                *search = Some(var);
+                Some(var)
+            }
+        };
+        var_idx.map(|i| Var { index: i + 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vmtf_order(branching: &Branching) -> Vec<usize> {
+        let Branching::Vmtf { next, first, .. } = branching else {
+            unreachable!();
+        };
+        std::iter::successors(*first, |&var| next[var]).collect()
+    }
+
+    #[test]
+    fn test_vmtf_moves_bumped_vars_to_front() {
+        let mut assignment = Assignment::empty(4);
+        let mut vmtf = Branching::new(BranchingHeuristic::Vmtf, 4);
+        assert_eq!(vmtf.next(&assignment), Some(Var { index: 4 }));
+
+        // Bumped variables keep their relative order.
+        let lits = [PackedLit::new(2, Val::True), PackedLit::new(0, Val::False)];
+        vmtf.bump(&lits, &assignment, 1);
+        assert_eq!(vmtf_order(&vmtf), vec![1, 3, 0, 2]);
+        assert_eq!(vmtf.next(&assignment), Some(Var { index: 3 }));
+
+        // Assigned variables are skipped, and found again once unassigned.
+        assignment.set(Var { index: 3 }, Val::True, 1);
+        vmtf.assigned(2);
+        assignment.set(Var { index: 1 }, Val::True, 1);
+        vmtf.assigned(0);
+        assert_eq!(vmtf.next(&assignment), Some(Var { index: 4 }));
+        assignment.unassign(PackedLit::new(2, Val::True));
+        vmtf.unassigned(2);
+        assert_eq!(vmtf.next(&assignment), Some(Var { index: 3 }));
+    }
+
+    #[test]
+    fn test_chb_prefers_vars_in_recent_conflicts() {
+        let assignment = Assignment::empty(3);
+        let mut chb = Branching::new(BranchingHeuristic::Chb, 3);
+        let lits = [PackedLit::new(0, Val::True), PackedLit::new(2, Val::True)];
+        for var_idx in 0..3 {
+            chb.assigned(var_idx);
+        }
+        chb.bump(&lits[1..], &assignment, 10);
+        // Variable 3 was in a conflict just now, variable 1 never was.
+        chb.propagated(lits, true, 11);
+        for var_idx in 0..3 {
+            chb.unassigned(var_idx);
+        }
+        assert_eq!(chb.next(&assignment), Some(Var { index: 3 }));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::formula::*;
+use crate::solver_state::ClauseId;
+
+/// Compact handle to a clause stored in a `ClauseDb`. Only valid until the
+/// next `collect_garbage`, which hands out a relocation map for live handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClauseRef(u32);
+
+#[derive(Clone, Debug, PartialEq)]
+struct ClauseHeader {
+    id: ClauseId,
+    start: u32,
+    len: u32,
+    deleted: bool,
+}
+
+/// New handles for the clauses that survived a `collect_garbage`.
+pub struct Relocation(Vec<Option<ClauseRef>>);
+
+impl Relocation {
+    /// The new handle of a clause, or `None` if it was freed.
+    pub fn get(&self, cref: ClauseRef) -> Option<ClauseRef> {
+        self.0[cref.0 as usize]
+    }
+}
+
+// Every clause's literals live back to back in one arena, so the solver can
+// pass clauses around by reference instead of cloning them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClauseDb {
+    headers: Vec<ClauseHeader>,
+    arena: Vec<PackedLit>,
+    wasted: usize,
+}
+
+impl ClauseDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn alloc(&mut self, id: ClauseId, literals: &[PackedLit]) -> ClauseRef {
+        let cref = ClauseRef(self.headers.len() as u32);
+        self.headers.push(ClauseHeader {
+            id,
+            start: self.arena.len() as u32,
+            len: literals.len() as u32,
+            deleted: false,
+        });
+        self.arena.extend_from_slice(literals);
+        cref
+    }
+
+    pub fn literals(&self, cref: ClauseRef) -> &[PackedLit] {
+        let header = &self.headers[cref.0 as usize];
+        &self.arena[header.start as usize..(header.start + header.len) as usize]
+    }
+
+    // Propagation reorders literals so that the watched ones come first.
+    pub fn literals_mut(&mut self, cref: ClauseRef) -> &mut [PackedLit] {
+        let header = &self.headers[cref.0 as usize];
+        &mut self.arena[header.start as usize..(header.start + header.len) as usize]
+    }
+
+    pub fn id(&self, cref: ClauseRef) -> ClauseId {
+        self.headers[cref.0 as usize].id
+    }
+
+    // Owned copy of a clause, for proof logging and display.
+    pub fn clause(&self, cref: ClauseRef) -> Clause {
+        Clause {
+            literals: self.literals(cref).iter().map(|&lit| lit.into()).collect(),
+        }
+    }
+
+    // The clause's space is only reclaimed by the next `collect_garbage`.
+    pub fn free(&mut self, cref: ClauseRef) {
+        let header = &mut self.headers[cref.0 as usize];
+        assert!(!header.deleted, "clause {} freed twice", header.id);
+        header.deleted = true;
+        self.wasted += header.len as usize;
+    }
+
+    pub fn is_freed(&self, cref: ClauseRef) -> bool {
+        self.headers[cref.0 as usize].deleted
+    }
+
+    pub fn wasted(&self) -> usize {
+        self.wasted
+    }
+
+    /// Compact the arena, dropping freed clauses. Every handle held by the
+    /// caller must be passed through the returned relocation.
+    pub fn collect_garbage(&mut self) -> Relocation {
+        let mut relocation = Vec::with_capacity(self.headers.len());
+        let mut headers = Vec::with_capacity(self.headers.len());
+        let mut arena = Vec::with_capacity(self.arena.len() - self.wasted);
+        for header in &self.headers {
+            if header.deleted {
+                relocation.push(None);
+                continue;
+            }
+            relocation.push(Some(ClauseRef(headers.len() as u32)));
+            let start = header.start as usize;
+            arena.extend_from_slice(&self.arena[start..start + header.len as usize]);
+            headers.push(ClauseHeader {
+                start: (arena.len() - header.len as usize) as u32,
+                ..header.clone()
+            });
+        }
+        self.headers = headers;
+        self.arena = arena;
+        self.wasted = 0;
+        Relocation(relocation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lits(values: &[isize]) -> Vec<PackedLit> {
+        values
+            .iter()
+            .map(|&v| {
+                let value = if v > 0 { Val::True } else { Val::False };
+                PackedLit::new(v.unsigned_abs() - 1, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_garbage_relocates_live_clauses() {
+        let mut db = ClauseDb::new();
+        let a = db.alloc(1, &lits(&[1, 2]));
+        let b = db.alloc(2, &lits(&[-1, 3, 4]));
+        let c = db.alloc(5, &lits(&[-4]));
+
+        db.free(b);
+        assert!(db.is_freed(b) && !db.is_freed(a));
+        assert_eq!(db.wasted(), 3);
+        let relocation = db.collect_garbage();
+        assert_eq!(db.wasted(), 0);
+
+        assert_eq!(relocation.get(b), None);
+        let a = relocation.get(a).unwrap();
+        let c = relocation.get(c).unwrap();
+        assert_eq!((db.id(a), db.literals(a)), (1, lits(&[1, 2]).as_slice()));
+        assert_eq!((db.id(c), db.literals(c)), (5, lits(&[-4]).as_slice()));
+        assert_eq!(db.arena.len(), 3);
+    }
+}
@@ -0,0 +1,285 @@
+// Clause elimination: removing clauses without deriving anything new.
+// Blocked clauses are not implied by the rest of the formula, so each removed
+// clause goes onto the reconstruction stack with the literal that can be
+// flipped to satisfy it again. Hidden and asymmetric tautologies are implied,
+// but are recorded the same way, which costs nothing at model completion.
+
+use itertools::Itertools;
+use log::info;
+
+use crate::clause_db::ClauseRef;
+use crate::formula::*;
+use crate::preprocess::is_satisfied;
+use crate::solver_state::*;
+
+// Clauses a single tautology check may visit before it gives up.
+const PROPAGATION_LIMIT: usize = 1000;
+
+/// Remove hidden tautologies, then asymmetric tautologies, then blocked
+/// clauses. Returns the number of clauses removed.
+pub fn eliminate_clauses(state: &mut SolverState) -> usize {
+    eliminate_hidden_tautologies(state)
+        + eliminate_asymmetric_tautologies(state)
+        + eliminate_blocked_clauses(state)
+}
+
+/// Hidden tautology elimination: remove every clause whose negation
+/// propagates to a conflict through binary clauses alone.
+///
+/// Must be called at decision level 0 before any clause is learned. Returns
+/// the number of clauses removed.
+pub fn eliminate_hidden_tautologies(state: &mut SolverState) -> usize {
+    let removed = eliminate_tautologies(state, true);
+    info!("Removed {removed} hidden tautologies");
+    removed
+}
+
+/// Asymmetric tautology elimination: remove every clause whose negation
+/// propagates to a conflict through the other clauses.
+///
+/// Must be called at decision level 0 before any clause is learned. Returns
+/// the number of clauses removed.
+pub fn eliminate_asymmetric_tautologies(state: &mut SolverState) -> usize {
+    let removed = eliminate_tautologies(state, false);
+    info!("Removed {removed} asymmetric tautologies");
+    removed
+}
+
+/// Blocked clause elimination: remove every clause containing a literal
+/// whose resolvents with all clauses containing its negation are
+/// tautologies. Flipping that literal repairs a model that falsifies the
+/// clause without falsifying any other.
+///
+/// Must be called at decision level 0 before any clause is learned. Returns
+/// the number of clauses removed.
+pub fn eliminate_blocked_clauses(state: &mut SolverState) -> usize {
+    if state.propagate().is_some() {
+        return 0;
+    }
+    let (clauses, occurrences) = occurrence_lists(state);
+
+    let mut marked = vec![false; state.num_vars * 2];
+    let mut removed = 0;
+    for cref in clauses {
+        let literals = state.clauses.literals(cref).to_vec();
+        for lit in &literals {
+            marked[lit.index()] = true;
+        }
+        // Resolving on `lit` with D is a tautology if D contains the
+        // negation of another literal of C.
+        let blocking = literals.iter().copied().find(|&lit| {
+            state.assignment.value(lit).is_none()
+                && occurrences[lit.not().index()]
+                    .iter()
+                    .filter(|&&d| !state.clauses.is_freed(d))
+                    .all(|&d| {
+                        state
+                            .clauses
+                            .literals(d)
+                            .iter()
+                            .any(|&other| other != lit.not() && marked[other.not().index()])
+                    })
+        });
+        for lit in &literals {
+            marked[lit.index()] = false;
+        }
+
+        if let Some(lit) = blocking {
+            state.reconstruction.push(lit, literals);
+            state.remove_clause(cref);
+            removed += 1;
+        }
+    }
+
+    finish(state, removed);
+    info!("Removed {removed} blocked clauses");
+    removed
+}
+
+fn eliminate_tautologies(state: &mut SolverState, binary_only: bool) -> usize {
+    if state.propagate().is_some() {
+        return 0;
+    }
+    let (clauses, occurrences) = occurrence_lists(state);
+
+    let mut values = vec![None; state.num_vars * 2];
+    let mut removed = 0;
+    for cref in clauses {
+        if binary_only && state.clauses.literals(cref).len() == 2 {
+            // Its negation only meets the binary clauses through `cref` itself.
+            continue;
+        }
+        if negation_conflicts(state, &occurrences, cref, binary_only, &mut values) {
+            let literals = state.clauses.literals(cref).to_vec();
+            state.reconstruction.push(literals[0], literals);
+            state.remove_clause(cref);
+            removed += 1;
+        }
+    }
+
+    finish(state, removed);
+    removed
+}
+
+// The original clauses not satisfied at level 0, and the lists of those
+// containing each literal.
+fn occurrence_lists(state: &SolverState) -> (Vec<ClauseRef>, Vec<Vec<ClauseRef>>) {
+    let clauses = state
+        .original_clauses()
+        .iter()
+        .copied()
+        .filter(|&cref| !is_satisfied(state, cref))
+        .collect_vec();
+    let mut occurrences = vec![vec![]; state.num_vars * 2];
+    for &cref in &clauses {
+        for lit in state.clauses.literals(cref) {
+            occurrences[lit.index()].push(cref);
+        }
+    }
+    (clauses, occurrences)
+}
+
+fn finish(state: &mut SolverState, removed: usize) {
+    if removed > 0 {
+        state.finish_simplification();
+    }
+    state.stats.eliminated_clauses += removed as u64;
+}
+
+// Whether unit propagation on the negation of `cref`, over the level-0
+// assignment and every other clause that is still there, finds a conflict.
+// `values` holds the literals assigned on top of level 0, and is left all
+// `None` again.
+fn negation_conflicts(
+    state: &SolverState,
+    occurrences: &[Vec<ClauseRef>],
+    cref: ClauseRef,
+    binary_only: bool,
+    values: &mut [Option<bool>],
+) -> bool {
+    fn assign(values: &mut [Option<bool>], trail: &mut Vec<PackedLit>, lit: PackedLit) {
+        values[lit.index()] = Some(true);
+        values[lit.not().index()] = Some(false);
+        trail.push(lit);
+    }
+
+    let mut trail = vec![];
+    for &lit in state.clauses.literals(cref) {
+        if state.assignment.value(lit).is_none() {
+            assign(values, &mut trail, lit.not());
+        }
+    }
+
+    let mut conflict = false;
+    let mut visited = 0;
+    let mut head = 0;
+    'propagate: while head < trail.len() {
+        let lit = trail[head];
+        head += 1;
+        for &d in &occurrences[lit.not().index()] {
+            let literals = state.clauses.literals(d);
+            if d == cref || state.clauses.is_freed(d) || (binary_only && literals.len() != 2) {
+                continue;
+            }
+            visited += 1;
+            if visited > PROPAGATION_LIMIT {
+                break 'propagate;
+            }
+            let mut unassigned = literals.iter().copied().filter(|&other| {
+                state.assignment.value(other).or(values[other.index()]) != Some(false)
+            });
+            match (unassigned.next(), unassigned.next()) {
+                (None, _) => {
+                    conflict = true;
+                    break 'propagate;
+                }
+                (Some(other), None) if values[other.index()].is_none() => {
+                    // A literal true at level 0 would have satisfied `d`.
+                    assign(values, &mut trail, other);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for lit in trail {
+        values[lit.index()] = None;
+        values[lit.not().index()] = None;
+    }
+    conflict
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_dimacs_str;
+
+    // Every model of what is left, once repaired, satisfies the original
+    // formula, and there is one whenever the original formula has a model.
+    fn assert_models_repaired(cnf: &CnfFormula, state: &SolverState) {
+        let simplified = state.formula();
+        let mut satisfiable = false;
+        for mut assignment in Assignment::every_possible(cnf.num_vars) {
+            if check_assignment(cnf, &assignment) {
+                satisfiable = true;
+            }
+            if check_assignment(&simplified, &assignment) {
+                state.reconstruction.extend(&mut assignment);
+                assert!(check_assignment(cnf, &assignment));
+            }
+        }
+        assert_eq!(
+            satisfiable,
+            Assignment::every_possible(cnf.num_vars)
+                .any(|assignment| check_assignment(&simplified, &assignment))
+        );
+    }
+
+    #[test]
+    fn test_bce() {
+        let cnf =
+            parse_dimacs_str(b"\np cnf 4 5\n1 2 0\n-1 -2 0\n2 3 0\n-2 -3 0\n-3 4 -1 0").unwrap();
+        let mut bce = SolverState::from_cnf(&cnf);
+        // 1 2 is only blocked once the clauses after it are gone.
+        assert_eq!(eliminate_blocked_clauses(&mut bce), 4);
+        let expected = parse_dimacs_str(b"\np cnf 4 1\n1 2 0").unwrap();
+        assert_eq!(bce.get_equivalent_clauses(), expected.clauses);
+        assert_models_repaired(&cnf, &bce);
+    }
+
+    #[test]
+    fn test_bce_keeps_clauses_without_blocking_literal() {
+        // Every literal resolves with some clause into a non-tautology.
+        let cnf = parse_dimacs_str(b"\np cnf 2 4\n1 2 0\n1 -2 0\n-1 2 0\n-1 -2 0").unwrap();
+        let mut bce = SolverState::from_cnf(&cnf);
+        assert_eq!(eliminate_blocked_clauses(&mut bce), 0);
+        assert_eq!(bce.get_equivalent_clauses(), cnf.clauses);
+        assert_models_repaired(&cnf, &bce);
+    }
+
+    #[test]
+    fn test_hte() {
+        // -1 implies 2 implies 3 through binary clauses, so 1 3 4 is a hidden
+        // tautology.
+        let cnf = parse_dimacs_str(b"\np cnf 4 4\n1 2 0\n-2 3 0\n1 3 4 0\n-3 -4 1 0").unwrap();
+        let mut hte = SolverState::from_cnf(&cnf);
+        assert_eq!(eliminate_hidden_tautologies(&mut hte), 1);
+        let expected = parse_dimacs_str(b"\np cnf 4 3\n1 2 0\n-2 3 0\n-3 -4 1 0").unwrap();
+        assert_eq!(hte.get_equivalent_clauses(), expected.clauses);
+        assert_models_repaired(&cnf, &hte);
+    }
+
+    #[test]
+    fn test_ate() {
+        // Falsifying 1 2 3 makes 4 true through the first clause, which
+        // falsifies the second. Only the ternary clauses reach the conflict.
+        let cnf = parse_dimacs_str(b"\np cnf 4 3\n1 2 4 0\n3 -4 1 0\n1 2 3 0").unwrap();
+        let mut hte = SolverState::from_cnf(&cnf);
+        assert_eq!(eliminate_hidden_tautologies(&mut hte), 0);
+        let mut ate = SolverState::from_cnf(&cnf);
+        assert_eq!(eliminate_asymmetric_tautologies(&mut ate), 1);
+        let expected = parse_dimacs_str(b"\np cnf 4 2\n1 2 4 0\n3 -4 1 0").unwrap();
+        assert_eq!(ate.get_equivalent_clauses(), expected.clauses);
+        assert_models_repaired(&cnf, &ate);
+    }
+}
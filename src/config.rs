@@ -1,8 +1,31 @@
+use std::collections::VecDeque;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PolarityHeuristic {
     AlwaysFalse,
     AlwaysTrue,
     PhaseSaving,
+    // Follow the phases of the longest conflict-free trail since the last
+    // restart, and rephase every so often.
+    Target,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchingHeuristic {
+    // Exponential VSIDS: bump the variables of each learned clause, weighting
+    // every conflict `1 / decay` times as much as the one before.
+    Evsids { decay: f64 },
+    // Variable move-to-front: decide on the most recently bumped variable.
+    Vmtf,
+    // Conflict history-based: reward variables for being assigned shortly
+    // after taking part in a conflict, as in a multi-armed bandit.
+    Chb,
+}
+
+impl Default for BranchingHeuristic {
+    fn default() -> Self {
+        BranchingHeuristic::Evsids { decay: 0.95 }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -10,28 +33,103 @@ pub enum RestartStrategy {
     None,
     Luby { unit: u32 },
     Geometric { initial: u32, factor: f64 },
+    // Restart once the LBD of recent learned clauses averages `margin` times
+    // that of all of them, unless the trail is `blocking` times longer than
+    // it has recently been at a conflict.
+    Glucose { margin: f64, blocking: f64 },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DeletionStrategy {
     None,
-    Lbd { max_lbd: u32 },
-    Activity { fraction: f64 },
+    Lbd {
+        max_lbd: u32,
+    },
+    Activity {
+        fraction: f64,
+    },
+    // Keep clauses with LBD up to `core_lbd` for good, and those up to
+    // `tier2_lbd` while they are used between deletions. Of the rest, delete
+    // `fraction` with the lowest activity.
+    Tiered {
+        core_lbd: u32,
+        tier2_lbd: u32,
+        fraction: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClauseMinimization {
+    None,
+    // Drop literals implied by the rest of the learned clause through chains of reasons.
+    Recursive,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct SolverConfig {
+    pub branching: BranchingHeuristic,
     pub polarity: PolarityHeuristic,
     pub restart: RestartStrategy,
     pub deletion: DeletionStrategy,
+    pub minimization: ClauseMinimization,
+    // Simplify the formula with `preprocess` before search.
+    pub preprocess: bool,
+    // Log every clause learned, derived or deleted, so that an UNSAT result
+    // comes with a proof. Costs time and memory, so it is off unless asked for.
+    pub proof: bool,
+    // Restarts between rounds of subsuming learned clauses with the ones
+    // learned since the last round. Zero turns subsumption at restarts off.
+    pub subsumption_interval: u32,
+    // Literals that vivifying learned clauses may propagate in each round,
+    // and restarts between rounds. Zero turns vivification off.
+    pub vivification_budget: u32,
+    pub vivification_interval: u32,
+    // Literals that failed literal probing may propagate in each round, and
+    // restarts between rounds. Zero turns probing at restarts off.
+    pub probe_budget: u32,
+    pub probe_interval: u32,
+    // Seed for every random choice, so that runs with the same seed are
+    // reproducible.
+    pub seed: u64,
+    // Probabilities of deciding on a random variable instead of the
+    // branching heuristic's, and on a random polarity.
+    pub random_var_frequency: f64,
+    pub random_polarity_frequency: f64,
+    // Start from a random variable order instead of input order.
+    pub initial_shuffle: bool,
+    // After a conflict that would jump back more than `chronological_threshold`
+    // levels, backtrack a single level instead, keeping literals implied at
+    // the levels in between on the trail (Nadel & Ryvchin).
+    pub chronological_backtracking: bool,
+    pub chronological_threshold: u32,
+    // Keep the decision levels on restart that the branching heuristic would
+    // decide on again first. Restarts with a round of subsumption, probing or
+    // vivification still go back to level 0, which these need.
+    pub reuse_trail: bool,
 }
 
 impl Default for SolverConfig {
     fn default() -> Self {
         Self {
+            branching: BranchingHeuristic::default(),
             polarity: PolarityHeuristic::PhaseSaving,
             restart: RestartStrategy::Luby { unit: 100 },
             deletion: DeletionStrategy::Lbd { max_lbd: 6 },
+            minimization: ClauseMinimization::Recursive,
+            preprocess: false,
+            proof: false,
+            subsumption_interval: 4,
+            vivification_budget: 2_000,
+            vivification_interval: 4,
+            probe_budget: 20_000,
+            probe_interval: 4,
+            seed: 0,
+            random_var_frequency: 0.0,
+            random_polarity_frequency: 0.0,
+            initial_shuffle: false,
+            chronological_backtracking: false,
+            chronological_threshold: 100,
+            reuse_trail: false,
         }
     }
 }
@@ -61,12 +159,61 @@ impl LubySequence {
     }
 }
 
+// Average of the last `capacity` values pushed.
+struct WindowAverage {
+    values: VecDeque<u64>,
+    capacity: usize,
+    sum: u64,
+}
+
+impl WindowAverage {
+    fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+            sum: 0,
+        }
+    }
+
+    fn push(&mut self, value: u64) {
+        if self.values.len() == self.capacity {
+            self.sum -= self.values.pop_front().unwrap();
+        }
+        self.values.push_back(value);
+        self.sum += value;
+    }
+
+    fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    fn average(&self) -> f64 {
+        self.sum as f64 / self.values.len() as f64
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.sum = 0;
+    }
+}
+
+// Learned clauses in the short-term LBD average, and conflicts in the
+// average trail size, as in Glucose.
+const LBD_WINDOW: usize = 50;
+const TRAIL_WINDOW: usize = 5000;
+// Conflicts before restarts may be blocked.
+const BLOCKING_START: u64 = 10_000;
+
 /// Tracks restart timing for a given strategy, advancing the threshold after each restart.
 pub struct RestartScheduler {
     strategy: RestartStrategy,
     luby: LubySequence,
     next_restart: u32,
     geometric_gap: u32,
+    recent_lbds: WindowAverage,
+    lbd_sum: u64,
+    conflicts: u64,
+    trail_sizes: WindowAverage,
 }
 
 impl RestartScheduler {
@@ -76,17 +223,48 @@ impl RestartScheduler {
             RestartStrategy::None => (u32::MAX, 0),
             RestartStrategy::Luby { unit } => (luby.next() * unit, 0),
             RestartStrategy::Geometric { initial, .. } => (initial, initial),
+            RestartStrategy::Glucose { .. } => (u32::MAX, 0),
         };
         Self {
             strategy,
             luby,
             next_restart,
             geometric_gap,
+            recent_lbds: WindowAverage::new(LBD_WINDOW),
+            lbd_sum: 0,
+            conflicts: 0,
+            trail_sizes: WindowAverage::new(TRAIL_WINDOW),
+        }
+    }
+
+    /// Call for every learned clause with its LBD and the length of the trail
+    /// at the conflict it was learned from.
+    pub fn record_conflict(&mut self, lbd: u32, trail_len: usize) {
+        if let RestartStrategy::Glucose { blocking, .. } = self.strategy {
+            // A trail this long suggests a model may be near, so the restart
+            // that recent clauses call for is put off.
+            if self.conflicts > BLOCKING_START
+                && self.trail_sizes.is_full()
+                && trail_len as f64 > blocking * self.trail_sizes.average()
+            {
+                self.recent_lbds.clear();
+            }
         }
+        self.trail_sizes.push(trail_len as u64);
+        self.recent_lbds.push(lbd as u64);
+        self.lbd_sum += lbd as u64;
+        self.conflicts += 1;
     }
 
     pub fn should_restart(&self, conflict_count: u32) -> bool {
-        conflict_count >= self.next_restart
+        match self.strategy {
+            RestartStrategy::Glucose { margin, .. } => {
+                self.recent_lbds.is_full()
+                    && self.recent_lbds.average()
+                        > margin * (self.lbd_sum as f64 / self.conflicts as f64)
+            }
+            _ => conflict_count >= self.next_restart,
+        }
     }
 
     /// Call immediately after performing a restart, passing the current conflict count.
@@ -98,6 +276,10 @@ impl RestartScheduler {
                 self.geometric_gap = ((self.geometric_gap as f64) * factor).ceil() as u32;
                 self.geometric_gap
             }
+            RestartStrategy::Glucose { .. } => {
+                self.recent_lbds.clear();
+                u32::MAX
+            }
         };
         self.next_restart = conflict_count.saturating_add(gap);
     }
@@ -157,4 +339,40 @@ mod tests {
         assert!(!sched.should_restart(69));
         assert!(sched.should_restart(70));
     }
+
+    #[test]
+    fn test_restart_scheduler_glucose_follows_recent_lbds() {
+        let mut sched = RestartScheduler::new(RestartStrategy::Glucose {
+            margin: 1.25,
+            blocking: 1.4,
+        });
+        for _ in 0..LBD_WINDOW {
+            sched.record_conflict(4, 100);
+        }
+        assert!(!sched.should_restart(0));
+        // Recent clauses are getting worse than the average of all of them.
+        for _ in 0..LBD_WINDOW {
+            sched.record_conflict(12, 100);
+        }
+        assert!(sched.should_restart(0));
+        sched.advance(0);
+        // The short-term average starts over after a restart.
+        assert!(!sched.should_restart(0));
+    }
+
+    #[test]
+    fn test_restart_scheduler_glucose_blocks_on_long_trail() {
+        let mut sched = RestartScheduler::new(RestartStrategy::Glucose {
+            margin: 1.25,
+            blocking: 1.4,
+        });
+        for _ in 0..BLOCKING_START {
+            sched.record_conflict(4, 100);
+        }
+        for _ in 0..LBD_WINDOW - 1 {
+            sched.record_conflict(12, 100);
+        }
+        sched.record_conflict(12, 200);
+        assert!(!sched.should_restart(0));
+    }
 }
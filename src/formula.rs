@@ -55,6 +55,73 @@ impl std::fmt::Display for Lit {
     }
 }
 
+/// A literal packed into 32 bits as `2 * var + sign`, with variables counted
+/// from 0 and the low bit set for negative literals. The solver engine works
+/// on these; `Lit` is what goes in and out of it.
+///
+/// `index` is distinct for every literal and `not` only flips the low bit, so
+/// per-literal tables can be indexed directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackedLit(u32);
+
+impl PackedLit {
+    pub fn new(var_index: usize, value: Val) -> Self {
+        let sign = match value {
+            Val::True => 0,
+            Val::False => 1,
+        };
+        PackedLit((var_index as u32) << 1 | sign)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    // 0-based, unlike `Var::index`.
+    pub fn var_index(&self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    pub fn var(&self) -> Var {
+        Var {
+            index: self.var_index() + 1,
+        }
+    }
+
+    pub fn value(&self) -> Val {
+        if self.0 & 1 == 0 {
+            Val::True
+        } else {
+            Val::False
+        }
+    }
+
+    pub fn not(&self) -> Self {
+        PackedLit(self.0 ^ 1)
+    }
+}
+
+impl From<Lit> for PackedLit {
+    fn from(lit: Lit) -> Self {
+        PackedLit::new(lit.var.index - 1, lit.value)
+    }
+}
+
+impl From<PackedLit> for Lit {
+    fn from(lit: PackedLit) -> Self {
+        Lit {
+            var: lit.var(),
+            value: lit.value(),
+        }
+    }
+}
+
+impl std::fmt::Display for PackedLit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Lit::from(*self).fmt(f)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Clause {
     pub literals: Vec<Lit>,
@@ -77,3 +144,25 @@ impl std::fmt::Display for CnfFormula {
         write!(f, "({})", self.clauses.iter().join(" ^ "))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_lit_round_trip() {
+        let lit = Lit {
+            var: Var { index: 3 },
+            value: Val::False,
+        };
+        let packed = PackedLit::from(lit);
+        assert_eq!(packed.index(), 5);
+        assert_eq!(packed.var_index(), 2);
+        assert_eq!(Lit::from(packed), lit);
+        assert_eq!(Lit::from(packed.not()), lit.not());
+        assert_eq!(packed.not().not(), packed);
+        // Packing keeps the order of `Lit`: by variable, positive first.
+        assert!(PackedLit::from(lit.not()) < packed);
+        assert!(packed < PackedLit::new(3, Val::True));
+    }
+}
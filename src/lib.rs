@@ -1,7 +1,13 @@
+pub mod branching;
+pub mod clause_db;
+pub mod clause_elimination;
 pub mod config;
 pub mod formula;
 pub mod parser;
+pub mod preprocess;
+pub mod proof_check;
 pub mod random;
+pub mod rephase;
 pub mod solve_cdcl;
 pub mod solve_cnc;
 pub mod solve_simple;
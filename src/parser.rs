@@ -121,20 +121,98 @@ pub fn output_dimacs<W: io::Write>(
 
 pub fn output_drat<W: io::Write>(
     writer: &mut io::BufWriter<W>,
-    proof: &Vec<Clause>,
+    proof: &[ProofStep],
 ) -> io::Result<()> {
-    for clause in proof {
+    for step in proof {
+        let clause = match step {
+            ProofStep::Add { clause, .. } => clause,
+            ProofStep::Delete { clause, .. } => {
+                writer.write_all(b"d ")?;
+                clause
+            }
+        };
+        write_literals(writer, clause)?;
+        writer.write_all(b"0\n")?;
+    }
+    Ok(())
+}
+
+fn write_literals<W: io::Write>(writer: &mut io::BufWriter<W>, clause: &Clause) -> io::Result<()> {
+    for lit in &clause.literals {
+        writer.write_all(
+            format!(
+                "{}{} ",
+                if lit.value == Val::True { "" } else { "-" },
+                lit.var.index
+            )
+            .as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
+// LRAT: "<id> <literals> 0 <hints> 0" per lemma and "<id> d <ids> 0" per
+// deletion, where a deletion line reuses the ID of the latest lemma.
+pub fn output_lrat<W: io::Write>(
+    writer: &mut io::BufWriter<W>,
+    proof: &[ProofStep],
+) -> io::Result<()> {
+    let mut last_id = None;
+    for step in proof {
+        match step {
+            ProofStep::Add {
+                id,
+                clause,
+                hints,
+                rat_hints,
+            } => {
+                last_id = Some(*id);
+                writer.write_all(format!("{id} ").as_bytes())?;
+                write_literals(writer, clause)?;
+                writer.write_all(b"0 ")?;
+                for hint in hints {
+                    writer.write_all(format!("{hint} ").as_bytes())?;
+                }
+                // Each clause the lemma is RAT against, negated, then its hints.
+                for (candidate, hints) in rat_hints {
+                    writer.write_all(format!("-{candidate} ").as_bytes())?;
+                    for hint in hints {
+                        writer.write_all(format!("{hint} ").as_bytes())?;
+                    }
+                }
+                writer.write_all(b"0\n")?;
+            }
+            ProofStep::Delete { id, .. } => {
+                let line_id = last_id.unwrap_or(*id);
+                writer.write_all(format!("{line_id} d {id} 0\n").as_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Binary DRAT: each step is 'a' or 'd', then every literal as the unsigned
+// 2*var + sign in little-endian base-128 (high bit set on all but the last
+// byte), then a terminating zero byte.
+pub fn output_binary_drat<W: io::Write>(
+    writer: &mut io::BufWriter<W>,
+    proof: &[ProofStep],
+) -> io::Result<()> {
+    for step in proof {
+        let (tag, clause) = match step {
+            ProofStep::Add { clause, .. } => (b'a', clause),
+            ProofStep::Delete { clause, .. } => (b'd', clause),
+        };
+        writer.write_all(&[tag])?;
         for lit in &clause.literals {
-            writer.write_all(
-                format!(
-                    "{}{} ",
-                    if lit.value == Val::True { "" } else { "-" },
-                    lit.var.index
-                )
-                .as_bytes(),
-            )?;
+            let mut encoded = 2 * lit.var.index + usize::from(lit.value == Val::False);
+            while encoded > 0x7f {
+                writer.write_all(&[(encoded & 0x7f) as u8 | 0x80])?;
+                encoded >>= 7;
+            }
+            writer.write_all(&[encoded as u8])?;
         }
-        writer.write_all(b"0\n")?;
+        writer.write_all(&[0])?;
     }
     Ok(())
 }
@@ -161,6 +239,63 @@ mod tests {
         assert!(cnf.is_err());
     }
 
+    fn proof_fixture() -> Vec<ProofStep> {
+        let lit = |index: usize, value: Val| Lit {
+            var: Var { index },
+            value,
+        };
+        vec![
+            ProofStep::Add {
+                id: 4,
+                clause: Clause {
+                    literals: vec![lit(1, Val::True), lit(70, Val::False)],
+                },
+                hints: vec![1, 3],
+                rat_hints: vec![],
+            },
+            ProofStep::Delete {
+                id: 2,
+                clause: Clause {
+                    literals: vec![lit(2, Val::False)],
+                },
+            },
+            ProofStep::Add {
+                id: 5,
+                clause: Clause { literals: vec![] },
+                hints: vec![4, 1, 2],
+                rat_hints: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_output_drat_text() {
+        let mut writer = io::BufWriter::new(Vec::new());
+        output_drat(&mut writer, &proof_fixture()).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), b"1 -70 0\nd -2 0\n0\n");
+    }
+
+    #[test]
+    fn test_output_lrat() {
+        let mut writer = io::BufWriter::new(Vec::new());
+        output_lrat(&mut writer, &proof_fixture()).unwrap();
+        assert_eq!(
+            writer.into_inner().unwrap(),
+            b"4 1 -70 0 1 3 0\n4 d 2 0\n5 0 4 1 2 0\n"
+        );
+    }
+
+    #[test]
+    fn test_output_drat_binary() {
+        let mut writer = io::BufWriter::new(Vec::new());
+        output_binary_drat(&mut writer, &proof_fixture()).unwrap();
+        // -70 encodes as 141 = 0x8d, which takes two bytes: 0x8d, 0x01.
+        assert_eq!(
+            writer.into_inner().unwrap(),
+            [b'a', 2, 0x8d, 0x01, 0, b'd', 5, 0, b'a', 0]
+        );
+    }
+
     #[test]
     fn test_parse_normal() {
         let cnf = parse_dimacs_str(
@@ -0,0 +1,1337 @@
+// Simplifications applied to the formula before search, in the manner of
+// SatELite. They work on a `SolverState` at decision level 0, so that every
+// derived clause and deletion lands in the proof like any other.
+
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+use log::info;
+
+use crate::clause_elimination;
+use crate::formula::*;
+use crate::solver_state::*;
+
+/// Clauses removed by preprocessing, each with the literal it was removed
+/// for. Replaying them backwards turns a model of the simplified formula into
+/// a model of the original one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReconstructionStack {
+    clauses: Vec<(PackedLit, Vec<PackedLit>)>,
+}
+
+impl ReconstructionStack {
+    pub fn push(&mut self, pivot: PackedLit, literals: Vec<PackedLit>) {
+        self.clauses.push((pivot, literals));
+    }
+
+    /// Make every removed clause true, latest first, by flipping its pivot
+    /// wherever the assignment falsifies it.
+    pub fn extend(&self, assignment: &mut Assignment) {
+        for (pivot, literals) in self.clauses.iter().rev() {
+            if !literals
+                .iter()
+                .any(|&lit| assignment.value(lit) == Some(true))
+            {
+                assignment.assign(*pivot, u32::MAX);
+            }
+        }
+    }
+}
+
+/// Simplify the original clauses with every technique in this module.
+pub fn preprocess(state: &mut SolverState) {
+    let original = state.original_clauses().to_vec();
+    subsume(state, original.clone(), original);
+    probe(state, 0, PROBE_BUDGET, false);
+    substitute_equivalent_literals(state);
+    clause_elimination::eliminate_clauses(state);
+    eliminate_variables(state);
+    add_variables(state);
+}
+
+// Literals `probe` may propagate before search.
+const PROBE_BUDGET: usize = 1_000_000;
+
+// Variables occurring more often than this in either polarity are left
+// alone, as the number of resolvents to try grows with the product.
+const MAX_OCCURRENCES: usize = 16;
+
+/// Eliminate variables by clause distribution: replace every clause
+/// containing a variable with all their non-tautological resolvents on it,
+/// as long as there are no more resolvents than clauses. The removed clauses
+/// go onto the state's reconstruction stack.
+///
+/// Must be called at decision level 0 before any clause is learned. Unit
+/// resolvents are propagated as soon as they are derived, and a conflict ends
+/// the pass, leaving it for the next call to `propagate`. Returns the number
+/// of variables eliminated.
+pub fn eliminate_variables(state: &mut SolverState) -> usize {
+    if state.propagate().is_some() {
+        return 0;
+    }
+
+    let mut occurrences = occurrence_lists(state);
+    let positive = |var_index| PackedLit::new(var_index, Val::True);
+    let candidates = (0..state.num_vars)
+        .filter(|&var_index| state.assignment.value(positive(var_index)).is_none())
+        .sorted_by_key(|&var_index| {
+            let lit = positive(var_index);
+            occurrences[lit.index()].len() * occurrences[lit.not().index()].len()
+        })
+        .collect_vec();
+
+    let mut eliminated = 0;
+    for var_index in candidates {
+        let pivot = positive(var_index);
+        // Units derived along the way may have assigned it since.
+        if state.assignment.value(pivot).is_some() {
+            continue;
+        }
+        for lit in [pivot, pivot.not()] {
+            occurrences[lit.index()].retain(|&cref| !state.clauses.is_freed(cref));
+        }
+        let pos = &occurrences[pivot.index()];
+        let neg = &occurrences[pivot.not().index()];
+        if pos.len() > MAX_OCCURRENCES || neg.len() > MAX_OCCURRENCES {
+            continue;
+        }
+
+        let limit = pos.len() + neg.len();
+        let Some(resolvents) = bounded_resolvents(state, var_index, pos, neg, limit) else {
+            continue;
+        };
+        if resolvents.iter().any(|(literals, _)| literals.is_empty()) {
+            // Only complementary units resolve to nothing, and propagating
+            // them refutes the formula.
+            break;
+        }
+        let mut units = false;
+        for (literals, hints) in resolvents {
+            units |= literals.len() == 1;
+            let cref = state.derive_clause(literals, hints);
+            for lit in state.clauses.literals(cref) {
+                occurrences[lit.index()].push(cref);
+            }
+        }
+        for lit in [pivot, pivot.not()] {
+            for cref in std::mem::take(&mut occurrences[lit.index()]) {
+                state
+                    .reconstruction
+                    .push(lit, state.clauses.literals(cref).to_vec());
+                state.remove_clause(cref);
+            }
+        }
+        eliminated += 1;
+
+        if units {
+            // Propagate the units right away, so that no variable is
+            // eliminated with a unit clause on it.
+            state.finish_simplification();
+            if state.propagate().is_some() {
+                break;
+            }
+            occurrences = occurrence_lists(state);
+        }
+    }
+
+    state.finish_simplification();
+    info!("Eliminated {eliminated} variables");
+    eliminated
+}
+
+// The clauses each literal occurs in, once each however often it repeats
+// the literal, among the original clauses not yet satisfied at level 0.
+// Every reason on the trail is left out, and so is every tautology.
+fn occurrence_lists(state: &SolverState) -> Vec<Vec<ClauseRef>> {
+    let mut occurrences = vec![vec![]; state.num_vars * 2];
+    for &cref in state.original_clauses() {
+        let literals = state.clauses.literals(cref);
+        if !is_satisfied(state, cref) && !is_tautology(literals) {
+            for lit in literals.iter().unique() {
+                occurrences[lit.index()].push(cref);
+            }
+        }
+    }
+    occurrences
+}
+
+// Clauses `add_variables` may visit looking for matches before it gives up.
+const ADDITION_BUDGET: usize = 10_000_000;
+
+/// Bounded variable addition, in the manner of SimpleBVA: find literals
+/// l1..ln and clause remainders R1..Rm such that every clause (li ∨ Rj) is
+/// in the formula, and replace those n*m clauses with (x ∨ Rj) and (¬x ∨ li)
+/// for a fresh variable x, whenever that leaves fewer clauses. The new
+/// clauses are RAT on x, and the model never includes x, so nothing goes
+/// onto the reconstruction stack.
+///
+/// Must be called at decision level 0 before any clause is learned. Returns
+/// the number of variables added.
+pub fn add_variables(state: &mut SolverState) -> usize {
+    if state.propagate().is_some() {
+        return 0;
+    }
+
+    let mut occurrences = occurrence_lists(state);
+    // Most frequent literal first. Entries go stale as counts change, and
+    // are skipped unless they are still accurate.
+    let mut queue = (0..state.num_vars)
+        .flat_map(|var_index| [Val::True, Val::False].map(|val| PackedLit::new(var_index, val)))
+        .map(|lit| (occurrences[lit.index()].len(), lit))
+        .collect::<BinaryHeap<_>>();
+
+    let mut marked = vec![false; state.num_vars * 2];
+    let mut visited = 0;
+    let mut added = 0;
+    while let Some((count, lit)) = queue.pop() {
+        occurrences[lit.index()].retain(|&cref| !state.clauses.is_freed(cref));
+        if count != occurrences[lit.index()].len() {
+            continue;
+        }
+        if visited > ADDITION_BUDGET {
+            break;
+        }
+
+        // Each row holds a clause containing `lit`, followed by the clauses
+        // matching it for every other literal of `matched` in turn.
+        let mut matched = vec![lit];
+        let mut grid = occurrences[lit.index()]
+            .iter()
+            .filter(|&&cref| state.clauses.literals(cref).len() > 1)
+            .map(|&cref| vec![cref])
+            .collect_vec();
+        loop {
+            let mut candidates: HashMap<PackedLit, Vec<usize>> = HashMap::new();
+            let mut matches = HashMap::new();
+            let mut used = HashSet::new();
+            for (row, clauses) in grid.iter().enumerate() {
+                let c = clauses[0];
+                let literals = state.clauses.literals(c);
+                // Every match contains the rest of C, so the rarest literal
+                // among them is enough to find them all. A clause repeating
+                // `lit` may have no rest at all.
+                let Some(&rarest) = literals
+                    .iter()
+                    .filter(|&&other| other != lit)
+                    .min_by_key(|other| occurrences[other.index()].len())
+                else {
+                    continue;
+                };
+                for &other in literals {
+                    marked[other.index()] = true;
+                }
+                for &d in &occurrences[rarest.index()] {
+                    let d_literals = state.clauses.literals(d);
+                    visited += 1;
+                    if state.clauses.is_freed(d)
+                        || d_literals.len() != literals.len()
+                        || d_literals.contains(&lit)
+                    {
+                        continue;
+                    }
+                    let mut extra = d_literals
+                        .iter()
+                        .copied()
+                        .filter(|other| !marked[other.index()]);
+                    let (Some(other), None) = (extra.next(), extra.next()) else {
+                        continue;
+                    };
+                    if other == lit.not()
+                        || marked[other.not().index()]
+                        || matched.contains(&other)
+                        || matches.contains_key(&(row, other))
+                        || !used.insert(d)
+                    {
+                        continue;
+                    }
+                    candidates.entry(other).or_default().push(row);
+                    matches.insert((row, other), d);
+                }
+                for &other in literals {
+                    marked[other.index()] = false;
+                }
+            }
+
+            let best = candidates
+                .into_iter()
+                .max_by_key(|(other, rows)| (rows.len(), std::cmp::Reverse(*other)));
+            let Some((other, rows)) = best else {
+                break;
+            };
+            if reduction(matched.len() + 1, rows.len()) <= reduction(matched.len(), grid.len()) {
+                break;
+            }
+            matched.push(other);
+            grid = rows
+                .into_iter()
+                .map(|row| {
+                    let mut clauses = std::mem::take(&mut grid[row]);
+                    clauses.push(matches[&(row, other)]);
+                    clauses
+                })
+                .collect();
+        }
+        if reduction(matched.len(), grid.len()) <= 0 {
+            continue;
+        }
+
+        let x = PackedLit::new(state.new_auxiliary_var().index - 1, Val::True);
+        occurrences.extend([vec![], vec![]]);
+        marked.extend([false, false]);
+        let mut definitions = vec![];
+        for clauses in &grid {
+            let literals = std::iter::once(x)
+                .chain(
+                    state
+                        .clauses
+                        .literals(clauses[0])
+                        .iter()
+                        .copied()
+                        .filter(|&other| other != lit),
+                )
+                .collect_vec();
+            // Nothing contains ¬x yet.
+            definitions.push(state.derive_rat_clause(literals, vec![]));
+        }
+        for (column, &other) in matched.iter().enumerate() {
+            // The resolvent with (x ∨ Rj) is the clause being replaced.
+            let rat_hints = definitions
+                .iter()
+                .zip(&grid)
+                .map(|(&definition, clauses)| {
+                    (
+                        state.clauses.id(definition),
+                        vec![state.clauses.id(clauses[column])],
+                    )
+                })
+                .collect_vec();
+            definitions.push(state.derive_rat_clause(vec![x.not(), other], rat_hints));
+        }
+        for &cref in &definitions {
+            for other in state.clauses.literals(cref) {
+                occurrences[other.index()].push(cref);
+            }
+        }
+        for cref in grid.into_iter().flatten() {
+            state.remove_clause(cref);
+        }
+        for other in matched.into_iter().chain([x, x.not()]) {
+            let occurrences = &mut occurrences[other.index()];
+            occurrences.retain(|&cref| !state.clauses.is_freed(cref));
+            queue.push((occurrences.len(), other));
+        }
+        added += 1;
+    }
+
+    if added > 0 {
+        state.finish_simplification();
+    }
+    state.stats.added_vars += added as u64;
+    info!("Added {added} variables");
+    added
+}
+
+// How many clauses replacing a grid of `literals` by `clauses` saves.
+fn reduction(literals: usize, clauses: usize) -> isize {
+    (literals * clauses) as isize - (literals + clauses) as isize
+}
+
+/// Backward subsumption and self-subsuming resolution of `targets` by
+/// `candidates`. Each candidate C removes every target it is a subset of,
+/// and strengthens every target D it would be a subset of but for one
+/// literal whose negation is in D, by dropping that negation. Clauses
+/// satisfied at level 0 and tautologies are skipped.
+///
+/// Must be called at decision level 0, with the targets either all original
+/// or all learned, and the candidates among them. Returns the number of
+/// removed and strengthened clauses.
+pub fn subsume(
+    state: &mut SolverState,
+    candidates: Vec<ClauseRef>,
+    targets: Vec<ClauseRef>,
+) -> (usize, usize) {
+    if state.propagate().is_some() {
+        return (0, 0);
+    }
+
+    let candidates = candidates
+        .into_iter()
+        .filter(|&cref| !is_satisfied(state, cref) && !is_tautology(state.clauses.literals(cref)))
+        .sorted_by_key(|&cref| state.clauses.literals(cref).len())
+        .collect_vec();
+    let mut occurrences = vec![vec![]; state.num_vars * 2];
+    let mut signatures = HashMap::new();
+    for cref in targets {
+        if is_satisfied(state, cref) || is_tautology(state.clauses.literals(cref)) {
+            continue;
+        }
+        for lit in state.clauses.literals(cref).iter().unique() {
+            occurrences[lit.index()].push(cref);
+        }
+        signatures.insert(cref, signature(state.clauses.literals(cref)));
+    }
+
+    // Literals of the clause being checked against, stamped with its number.
+    let mut marks = vec![0; state.num_vars * 2];
+    let mut stamp = 0;
+    let (mut removed, mut strengthened) = (0, 0);
+    for c in candidates {
+        if state.clauses.is_freed(c) {
+            continue;
+        }
+        // Every clause C could act on contains one of the literals of C, or
+        // its negation, so the rarest of them is enough to find them all.
+        let literals = state.clauses.literals(c).to_vec();
+        let c_signature = signature(&literals);
+        let Some(&pivot) = literals.iter().min_by_key(|lit| {
+            occurrences[lit.index()].len() + occurrences[lit.not().index()].len()
+        }) else {
+            continue;
+        };
+        let candidates = occurrences[pivot.index()]
+            .iter()
+            .chain(&occurrences[pivot.not().index()])
+            .copied()
+            .filter(|&d| d != c && !state.clauses.is_freed(d))
+            .unique()
+            .collect_vec();
+
+        for d in candidates {
+            let d_literals = state.clauses.literals(d);
+            if d_literals.len() < literals.len() || c_signature & !signatures[&d] != 0 {
+                continue;
+            }
+            stamp += 1;
+            for lit in d_literals {
+                marks[lit.index()] = stamp;
+            }
+            let mut negated = None;
+            let subset = literals.iter().all(|lit| {
+                if marks[lit.index()] == stamp {
+                    true
+                } else if marks[lit.not().index()] == stamp && negated.is_none() {
+                    negated = Some(lit.not());
+                    true
+                } else {
+                    false
+                }
+            });
+            if !subset {
+                continue;
+            }
+
+            match negated {
+                None => {
+                    state.remove_clause(d);
+                    removed += 1;
+                }
+                Some(lit) => {
+                    // Unit propagation on the negated result makes C imply
+                    // the negation of `lit`, which falsifies D.
+                    let hints = vec![state.clauses.id(c), state.clauses.id(d)];
+                    let strengthened_d = state.strengthen_clause(d, lit, hints);
+                    let d_literals = state.clauses.literals(strengthened_d);
+                    signatures.insert(strengthened_d, signature(d_literals));
+                    for lit in d_literals.iter().unique() {
+                        occurrences[lit.index()].push(strengthened_d);
+                    }
+                    strengthened += 1;
+                }
+            }
+        }
+    }
+
+    if removed + strengthened > 0 {
+        state.finish_simplification();
+    }
+    state.stats.subsumed_clauses += removed as u64;
+    state.stats.strengthened_clauses += strengthened as u64;
+    info!("Subsumption removed {removed} clauses and strengthened {strengthened}");
+    (removed, strengthened)
+}
+
+/// Failed literal probing. Each unassigned variable, starting from the one
+/// at `start` and wrapping around, is decided both ways at level 1:
+/// - a polarity whose propagation conflicts is failed, so its negation is
+///   learned as a unit;
+/// - a literal implied by both polarities is learned as a unit;
+/// - a literal implied through a clause with at least two other literals
+///   false at level 1 gets a hyper-binary resolvent with the decision, so
+///   the binary implication graph finds it directly next time.
+///
+/// With `redundant`, the hyper-binary resolvents are added as learned clauses
+/// that deletion may remove again, rather than permanent ones.
+///
+/// Stops after `budget` propagated literals. Must be called at decision
+/// level 0. Returns the index of the variable to resume from.
+pub fn probe(state: &mut SolverState, start: usize, budget: usize, redundant: bool) -> usize {
+    debug_assert_eq!(state.decision_level, 0);
+    if state.num_vars == 0 || state.propagate().is_some() {
+        return start;
+    }
+    // Probing decisions must not disturb phase saving.
+    let phases = (1..=state.num_vars)
+        .map(|index| state.get_phase(Var { index }))
+        .collect_vec();
+
+    let mut propagated = 0;
+    let mut units = 0;
+    let resolvents_before = state.stats.hyper_binary_resolvents;
+    let mut next = start;
+    for i in 0..state.num_vars {
+        if propagated >= budget {
+            break;
+        }
+        let var_index = (start + i) % state.num_vars;
+        next = (var_index + 1) % state.num_vars;
+        let positive = PackedLit::new(var_index, Val::True);
+        if state.assignment.value(positive).is_some() {
+            continue;
+        }
+
+        let mut learned = vec![];
+        // Binary clauses only needed to justify a learned unit.
+        let mut temporary = vec![];
+        let pos = probe_literal(
+            state,
+            positive,
+            &HashSet::new(),
+            true,
+            redundant,
+            &mut propagated,
+        );
+        match pos {
+            Err(hints) => learned.push((positive.not(), hints)),
+            Ok(pos) => {
+                let implied = &pos.implied;
+                match probe_literal(
+                    state,
+                    positive.not(),
+                    implied,
+                    true,
+                    redundant,
+                    &mut propagated,
+                ) {
+                    Err(hints) => learned.push((positive, hints)),
+                    Ok(neg) if !neg.hints.is_empty() => {
+                        // Rerun the positive side for the hints of the literals
+                        // both sides imply.
+                        let wanted = neg.hints.keys().copied().collect();
+                        let Ok(pos) =
+                            probe_literal(state, positive, &wanted, false, false, &mut propagated)
+                        else {
+                            unreachable!("the same probe conflicted only the second time");
+                        };
+                        // In a fixed order, so that runs are reproducible.
+                        for (lit, neg_hints) in neg.hints.into_iter().sorted_by_key(|&(lit, _)| lit)
+                        {
+                            let pos_cref = state
+                                .derive_clause(vec![positive.not(), lit], pos.hints[&lit].clone());
+                            let neg_cref = state.derive_clause(vec![positive, lit], neg_hints);
+                            let hints =
+                                vec![state.clauses.id(pos_cref), state.clauses.id(neg_cref)];
+                            learned.push((lit, hints));
+                            temporary.extend([pos_cref, neg_cref]);
+                        }
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+
+        for (lit, hints) in learned {
+            let cref = state.derive_clause(vec![lit], hints);
+            state.assign_unitprop(lit, cref);
+            units += 1;
+        }
+        for cref in temporary {
+            state.remove_clause(cref);
+        }
+        let before = state.trail.len();
+        let conflict = state.propagate();
+        propagated += state.trail.len() - before;
+        if conflict.is_some() {
+            // The conflict stays queued for the caller to find.
+            break;
+        }
+    }
+
+    for (index, phase) in (1..=state.num_vars).zip(phases) {
+        state.set_phase(Var { index }, phase);
+    }
+    let resolvents = state.stats.hyper_binary_resolvents - resolvents_before;
+    if units + resolvents > 0 {
+        state.finish_simplification();
+    }
+    state.stats.probed_units += units;
+    info!("Probing learned {units} units and {resolvents} hyper-binary resolvents");
+    next
+}
+
+// What propagating a probed literal at level 1 implied.
+struct Implied {
+    implied: HashSet<PackedLit>,
+    // Hints deriving the binary clause with the negated probe, for each
+    // wanted literal that was implied.
+    hints: HashMap<PackedLit, Vec<ClauseId>>,
+}
+
+// Decide `lit` at level 1, propagate and backjump again, deriving any
+// hyper-binary resolvents along the way if asked to, as learned clauses if
+// `redundant`. A conflict gives the hints refuting `lit` instead.
+fn probe_literal(
+    state: &mut SolverState,
+    lit: PackedLit,
+    wanted: &HashSet<PackedLit>,
+    hyper_binary: bool,
+    redundant: bool,
+    propagated: &mut usize,
+) -> Result<Implied, Vec<ClauseId>> {
+    let start = state.trail.len();
+    state.decide(lit.var(), lit.value());
+    let conflict = state.propagate();
+    *propagated += state.trail.len() - start;
+    if let Some(cref) = conflict {
+        let mut hints = state.implication_hints(state.clauses.literals(cref));
+        hints.push(state.clauses.id(cref));
+        state.backjump_to_decision_level(0);
+        return Err(hints);
+    }
+
+    let mut implied = Implied {
+        implied: HashSet::new(),
+        hints: HashMap::new(),
+    };
+    let mut resolvents = vec![];
+    for trail_element in &state.trail[start + 1..] {
+        let implied_lit = trail_element.lit;
+        implied.implied.insert(implied_lit);
+        if wanted.contains(&implied_lit) {
+            let hints = state.implication_hints(&[implied_lit]);
+            implied.hints.insert(implied_lit, hints);
+        }
+        if hyper_binary && matches!(trail_element.reason, TrailReason::UnitProp(_)) {
+            let at_probe_level = state
+                .reason_literals(trail_element)
+                .filter(|&other| state.assignment.level(other) == Some(1))
+                .count();
+            if at_probe_level >= 2 {
+                let hints = state.implication_hints(&[implied_lit]);
+                resolvents.push((vec![lit.not(), implied_lit], hints));
+            }
+        }
+    }
+    state.backjump_to_decision_level(0);
+    state.stats.hyper_binary_resolvents += resolvents.len() as u64;
+    for (literals, hints) in resolvents {
+        if redundant {
+            state.derive_learned_clause(literals, hints, 2);
+        } else {
+            state.derive_clause(literals, hints);
+        }
+    }
+    Ok(implied)
+}
+
+/// Equivalent literal substitution. The literals of a strongly connected
+/// component of the binary implication graph all imply each other, so each
+/// is replaced by the component's representative, the literal of its lowest
+/// variable. Clauses that become tautologies or duplicates are removed, and
+/// the substitutions go onto the reconstruction stack.
+///
+/// If a literal is equivalent to its own negation, the formula is
+/// unsatisfiable: the negation of the representative is learned, and the
+/// conflict it propagates into stays queued for the caller to find.
+///
+/// Must be called at decision level 0 before any clause is learned. Returns
+/// the number of variables substituted.
+pub fn substitute_equivalent_literals(state: &mut SolverState) -> usize {
+    if state.propagate().is_some() {
+        return 0;
+    }
+
+    let literals = (0..state.num_vars)
+        .flat_map(|var_index| {
+            let lit = PackedLit::new(var_index, Val::True);
+            [lit, lit.not()]
+        })
+        .collect_vec();
+    let mut edges = vec![vec![]; literals.len()];
+    for &cref in state.original_clauses() {
+        if let [a, b] = *state.clauses.literals(cref) {
+            if state.assignment.value(a).is_none() && state.assignment.value(b).is_none() {
+                edges[a.not().index()].push((b, cref));
+                edges[b.not().index()].push((a, cref));
+            }
+        }
+    }
+    let component = strongly_connected_components(&edges);
+
+    let mut representative = HashMap::new();
+    for &lit in &literals {
+        representative
+            .entry(component[lit.index()])
+            .and_modify(|rep: &mut PackedLit| *rep = (*rep).min(lit))
+            .or_insert(lit);
+    }
+    let replacement = |lit: PackedLit| representative[&component[lit.index()]];
+
+    // Each representative reaches its whole component; the path to a literal
+    // is the chain of binary clauses showing the representative implies it.
+    let mut parent = vec![None; literals.len()];
+    for &rep in representative.values() {
+        let mut queue = VecDeque::from([rep]);
+        while let Some(lit) = queue.pop_front() {
+            for &(next, cref) in &edges[lit.index()] {
+                if next != rep
+                    && parent[next.index()].is_none()
+                    && component[next.index()] == component[rep.index()]
+                {
+                    parent[next.index()] = Some((lit, cref));
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    let path_hints = |state: &SolverState, mut lit: PackedLit| {
+        let mut hints = vec![];
+        while let Some((prev, cref)) = parent[lit.index()] {
+            hints.push(state.clauses.id(cref));
+            lit = prev;
+        }
+        hints.reverse();
+        hints
+    };
+
+    if let Some(&lit) = literals
+        .iter()
+        .find(|lit| component[lit.index()] == component[lit.not().index()])
+    {
+        let rep = replacement(lit);
+        info!("{} is equivalent to its own negation", Lit::from(rep));
+        let hints = path_hints(state, rep.not());
+        let cref = state.derive_clause(vec![rep.not()], hints);
+        state.assign_unitprop(rep.not(), cref);
+        state.finish_simplification();
+        return 0;
+    }
+
+    let mut substituted = 0;
+    for &lit in literals.iter().step_by(2) {
+        let rep = replacement(lit);
+        if rep != lit {
+            state.reconstruction.push(lit, vec![rep.not(), lit]);
+            state.reconstruction.push(lit.not(), vec![rep, lit.not()]);
+            substituted += 1;
+        }
+    }
+    if substituted == 0 {
+        return 0;
+    }
+
+    let (changed, unchanged): (Vec<_>, Vec<_>) = state
+        .original_clauses()
+        .iter()
+        .copied()
+        .filter(|&cref| !is_satisfied(state, cref))
+        .partition(|&cref| {
+            state
+                .clauses
+                .literals(cref)
+                .iter()
+                .any(|&lit| replacement(lit) != lit)
+        });
+    let mut existing: HashSet<_> = unchanged
+        .iter()
+        .map(|&cref| {
+            state
+                .clauses
+                .literals(cref)
+                .iter()
+                .copied()
+                .sorted()
+                .collect_vec()
+        })
+        .collect();
+    for &cref in &changed {
+        let mut hints = vec![];
+        let mut substituted_literals = vec![];
+        for &lit in state.clauses.literals(cref) {
+            let rep = replacement(lit);
+            if rep != lit {
+                // With the representative false, the chain makes `lit` false too.
+                hints.extend(path_hints(state, lit.not()));
+            }
+            substituted_literals.push(rep);
+        }
+        hints.push(state.clauses.id(cref));
+        substituted_literals.sort_unstable();
+        substituted_literals.dedup();
+        let tautology = substituted_literals
+            .windows(2)
+            .any(|w| w[0].var_index() == w[1].var_index());
+        if !tautology && existing.insert(substituted_literals.clone()) {
+            // Chains to the same representative share their start.
+            let hints = hints.into_iter().unique().collect();
+            state.derive_clause(substituted_literals, hints);
+        }
+    }
+    // Every derivation above may rely on any of the replaced clauses.
+    for cref in changed {
+        state.remove_clause(cref);
+    }
+
+    state.finish_simplification();
+    state.stats.substituted_vars += substituted as u64;
+    info!("Substituted {substituted} equivalent variables");
+    substituted
+}
+
+// Tarjan's algorithm, without recursion. Returns the component number of
+// every literal, where `edges` lists what each literal implies.
+fn strongly_connected_components(edges: &[Vec<(PackedLit, ClauseRef)>]) -> Vec<usize> {
+    let unvisited = usize::MAX;
+    let mut order = vec![unvisited; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut component = vec![unvisited; edges.len()];
+    let mut stack = vec![];
+    let mut visited = 0;
+    let mut num_components = 0;
+    for root in 0..edges.len() {
+        if order[root] != unvisited {
+            continue;
+        }
+        // Each node being visited, with the number of its edges followed.
+        let mut frames = vec![(root, 0)];
+        order[root] = visited;
+        low[root] = visited;
+        visited += 1;
+        stack.push(root);
+        while let Some(frame) = frames.last_mut() {
+            let node = frame.0;
+            if let Some(&(next, _)) = edges[node].get(frame.1) {
+                frame.1 += 1;
+                let next = next.index();
+                if order[next] == unvisited {
+                    order[next] = visited;
+                    low[next] = visited;
+                    visited += 1;
+                    stack.push(next);
+                    frames.push((next, 0));
+                } else if component[next] == unvisited {
+                    // Still on the stack, so part of the current path's component.
+                    low[node] = low[node].min(order[next]);
+                }
+                continue;
+            }
+            frames.pop();
+            if let Some(&(caller, _)) = frames.last() {
+                low[caller] = low[caller].min(low[node]);
+            }
+            if low[node] == order[node] {
+                while let Some(member) = stack.pop() {
+                    component[member] = num_components;
+                    if member == node {
+                        break;
+                    }
+                }
+                num_components += 1;
+            }
+        }
+    }
+    component
+}
+
+/// Vivify the clauses in `candidates`: assume the negations of a clause's
+/// literals one at a time and propagate. A literal that is already false is
+/// redundant, and a literal that is already true or a conflict makes the
+/// rest of the clause redundant. Whatever is left replaces the clause.
+///
+/// Stops after `budget` propagated literals. Must be called at decision
+/// level 0. Returns the number of literals removed.
+pub fn vivify(state: &mut SolverState, candidates: Vec<ClauseRef>, budget: usize) -> usize {
+    debug_assert_eq!(state.decision_level, 0);
+    if state.propagate().is_some() {
+        return 0;
+    }
+    // Vivifying decisions must not disturb phase saving.
+    let phases = (1..=state.num_vars)
+        .map(|index| state.get_phase(Var { index }))
+        .collect_vec();
+
+    let mut propagated = 0;
+    let mut shortened = vec![];
+    for cref in candidates {
+        if propagated >= budget {
+            break;
+        }
+        if is_satisfied(state, cref) {
+            continue;
+        }
+        let literals = state.clauses.literals(cref).to_vec();
+        let start = state.trail.len();
+        let mut kept = vec![];
+        let mut hints = None;
+        for &lit in &literals {
+            match state.assignment.value(lit) {
+                Some(false) => {}
+                Some(true) => {
+                    kept.push(lit);
+                    hints = Some(state.implication_hints(&[lit]));
+                    break;
+                }
+                None => {
+                    kept.push(lit);
+                    state.decide(lit.var(), lit.not().value());
+                    if let Some(conflict) = state.propagate() {
+                        let mut conflict_hints =
+                            state.implication_hints(state.clauses.literals(conflict));
+                        conflict_hints.push(state.clauses.id(conflict));
+                        hints = Some(conflict_hints);
+                        break;
+                    }
+                }
+            }
+        }
+        propagated += state.trail.len() - start;
+        if kept.len() < literals.len() {
+            // Without a conflict or a true literal, the clause itself ends
+            // up falsified.
+            let hints = hints.unwrap_or_else(|| {
+                let mut hints = state.implication_hints(&literals);
+                hints.push(state.clauses.id(cref));
+                hints
+            });
+            shortened.push((cref, kept, hints));
+        }
+        state.backjump_to_decision_level(0);
+    }
+
+    for (index, phase) in (1..=state.num_vars).zip(phases) {
+        state.set_phase(Var { index }, phase);
+    }
+    let mut removed = 0;
+    if !shortened.is_empty() {
+        // Any of the old clauses may be among the hints of another.
+        let mut old = vec![];
+        for (cref, kept, hints) in shortened {
+            removed += state.clauses.literals(cref).len() - kept.len();
+            state.derive_subclause(cref, kept, hints);
+            old.push(cref);
+        }
+        for cref in old {
+            state.remove_clause(cref);
+        }
+        state.finish_simplification();
+    }
+    state.stats.vivified_literals += removed as u64;
+    info!("Vivification removed {removed} literals");
+    removed
+}
+
+// One bit per variable modulo 64: if C is a subset of D, possibly with one
+// literal negated, every bit of C is also set for D.
+fn signature(literals: &[PackedLit]) -> u64 {
+    literals
+        .iter()
+        .fold(0, |signature, lit| signature | 1 << (lit.var_index() % 64))
+}
+
+/// Whether a literal of the clause is true at the current decision level.
+pub fn is_satisfied(state: &SolverState, cref: ClauseRef) -> bool {
+    state
+        .clauses
+        .literals(cref)
+        .iter()
+        .any(|&lit| state.assignment.value(lit) == Some(true))
+}
+
+// Whether the clause contains some literal along with its negation.
+fn is_tautology(literals: &[PackedLit]) -> bool {
+    literals.iter().any(|lit| literals.contains(&lit.not()))
+}
+
+// Every non-tautological resolvent on the variable of a clause in `pos` with
+// one in `neg`, with the two clauses as its hints, or `None` if there are
+// more than `limit`.
+fn bounded_resolvents(
+    state: &SolverState,
+    var_index: usize,
+    pos: &[ClauseRef],
+    neg: &[ClauseRef],
+    limit: usize,
+) -> Option<Vec<(Vec<PackedLit>, Vec<ClauseId>)>> {
+    let mut resolvents = vec![];
+    for &c in pos {
+        for &d in neg {
+            let mut resolvent = state
+                .clauses
+                .literals(c)
+                .iter()
+                .chain(state.clauses.literals(d))
+                .copied()
+                .filter(|lit| lit.var_index() != var_index)
+                .collect_vec();
+            resolvent.sort_unstable();
+            resolvent.dedup();
+            // After sorting, x and -x are adjacent.
+            if resolvent
+                .windows(2)
+                .any(|w| w[0].var_index() == w[1].var_index())
+            {
+                continue;
+            }
+            if resolvents.len() == limit {
+                return None;
+            }
+            // The clause with the positive pivot becomes unit first.
+            resolvents.push((resolvent, vec![state.clauses.id(c), state.clauses.id(d)]));
+        }
+    }
+    Some(resolvents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DeletionStrategy, SolverConfig};
+    use crate::parser::{output_lrat, parse_dimacs_str};
+    use crate::proof_check::{check_lrat, Verdict};
+    use crate::solve_cdcl::solve_cdcl;
+    use std::io;
+
+    fn packed_lit(value: isize) -> PackedLit {
+        let val = if value > 0 { Val::True } else { Val::False };
+        PackedLit::new(value.unsigned_abs() - 1, val)
+    }
+
+    #[test]
+    fn test_eliminate_variable_replaces_clauses_with_resolvents() {
+        // x1 occurs least, and resolving its two clauses gives one clause.
+        let cnf = parse_dimacs_str(b"p cnf 3 4\n1 2 0\n-1 3 0\n-2 -3 0\n2 3 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        assert!(eliminate_variables(&mut state) >= 1);
+        assert!(state
+            .formula()
+            .clauses
+            .iter()
+            .all(|clause| clause.literals.iter().all(|lit| lit.var.index != 1)));
+        let proof = state.take_proof().unwrap();
+        assert!(matches!(
+            &proof[0],
+            ProofStep::Add { hints, .. } if *hints == [1, 2]
+        ));
+    }
+
+    #[test]
+    fn test_eliminate_variables_finds_complementary_units() {
+        // Eliminating x2 gives the unit 1 and eliminating x3 gives -1.
+        let cnf = parse_dimacs_str(b"p cnf 3 4\n1 2 0\n1 -2 0\n-1 3 0\n-1 -3 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        eliminate_variables(&mut state);
+        assert!(state.propagate().is_some());
+
+        let config = SolverConfig {
+            preprocess: true,
+            proof: true,
+            ..SolverConfig::default()
+        };
+        let result = solve_cdcl(&cnf, &config);
+        let mut writer = io::BufWriter::new(Vec::new());
+        output_lrat(&mut writer, result.unsat_proof().unwrap()).unwrap();
+        assert_eq!(
+            check_lrat(&cnf, &writer.into_inner().unwrap()[..]).unwrap(),
+            Verdict::Verified
+        );
+    }
+
+    #[test]
+    fn test_eliminate_variables_with_repeated_literals() {
+        let cnf = parse_dimacs_str(b"p cnf 2 2\n1 2 1 2 0\n-2 -1 -1 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        assert_eq!(eliminate_variables(&mut state), 2);
+        assert!(check_assignment(&cnf, &state.model()));
+    }
+
+    #[test]
+    fn test_add_variables_factors_at_most_one() {
+        // Pairwise at-most-one over six variables: -1, -2 and -3 each meet
+        // -4, -5 and -6, so those nine clauses become six.
+        let cnf = parse_dimacs_str(
+            b"p cnf 6 15\n-1 -2 0\n-1 -3 0\n-1 -4 0\n-1 -5 0\n-1 -6 0\n-2 -3 0\n-2 -4 0\n\
+              -2 -5 0\n-2 -6 0\n-3 -4 0\n-3 -5 0\n-3 -6 0\n-4 -5 0\n-4 -6 0\n-5 -6 0",
+        )
+        .unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        assert_eq!(add_variables(&mut state), 1);
+        let simplified = state.formula();
+        assert_eq!(simplified.num_vars, 7);
+        assert_eq!(simplified.clauses.len(), 12);
+
+        // The models are the same once x7 is dropped.
+        let models = Assignment::every_possible(7)
+            .filter(|assignment| check_assignment(&simplified, assignment))
+            .map(|mut assignment| {
+                assignment.truncate(6);
+                assert!(check_assignment(&cnf, &assignment));
+                assignment.to_string()
+            })
+            .unique()
+            .count();
+        assert_eq!(models, 7);
+
+        let proof = state.take_proof().unwrap();
+        let mut writer = io::BufWriter::new(Vec::new());
+        output_lrat(&mut writer, &proof).unwrap();
+        assert_eq!(
+            check_lrat(&cnf, &writer.into_inner().unwrap()[..]).unwrap(),
+            Verdict::Incomplete
+        );
+    }
+
+    #[test]
+    fn test_add_variables_with_duplicate_clauses() {
+        // The row of 4 2 matches 2 3 on 3 twice, and must only take it once.
+        let cnf = parse_dimacs_str(b"p cnf 6 7\n4 1 0\n4 2 0\n4 5 0\n4 6 0\n1 3 0\n2 3 0\n2 3 0")
+            .unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        add_variables(&mut state);
+
+        let proof = state.take_proof().unwrap();
+        let mut writer = io::BufWriter::new(Vec::new());
+        output_lrat(&mut writer, &proof).unwrap();
+        assert_eq!(
+            check_lrat(&cnf, &writer.into_inner().unwrap()[..]).unwrap(),
+            Verdict::Incomplete
+        );
+    }
+
+    #[test]
+    fn test_add_variables_with_repeated_literal() {
+        let cnf = parse_dimacs_str(b"p cnf 9 2\n-9 -9 0\n1 2 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        assert_eq!(add_variables(&mut state), 0);
+    }
+
+    #[test]
+    fn test_subsume_removes_and_strengthens() {
+        // 1 2 subsumes 1 2 3, and strengthens -1 2 4 to 2 4.
+        let cnf = parse_dimacs_str(b"p cnf 4 3\n1 2 0\n1 2 3 0\n-1 2 4 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        let original = state.original_clauses().to_vec();
+        assert_eq!(subsume(&mut state, original.clone(), original), (1, 1));
+        let mut clauses = state
+            .formula()
+            .clauses
+            .iter()
+            .map(|clause| clause.literals.iter().map(|lit| lit.to_string()).join(" "))
+            .collect_vec();
+        clauses.sort();
+        assert_eq!(clauses, ["x1 x2", "x2 x4"]);
+        let proof = state.take_proof().unwrap();
+        let added = proof
+            .iter()
+            .filter_map(|step| match step {
+                ProofStep::Add { hints, .. } => Some(hints.clone()),
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(added, [[1, 3]]);
+    }
+
+    #[test]
+    fn test_subsume_skips_tautologies() {
+        // 1 -1 would otherwise strengthen 1 2 to 2 and -1 -2 to -2.
+        let cnf = parse_dimacs_str(b"p cnf 2 3\n1 2 0\n1 -1 0\n-1 -2 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        let original = state.original_clauses().to_vec();
+        assert_eq!(subsume(&mut state, original.clone(), original), (0, 0));
+
+        let config = SolverConfig {
+            preprocess: true,
+            ..SolverConfig::default()
+        };
+        let model = solve_cdcl(&cnf, &config).into_assignment().unwrap();
+        assert!(check_assignment(&cnf, &model));
+    }
+
+    #[test]
+    fn test_subsume_with_repeated_literals() {
+        // -4 -2 -3 -2 shows up twice among the clauses containing -2.
+        let cnf = parse_dimacs_str(
+            b"p cnf 5 10\n5 -3 1 0\n-2 3 0\n-4 -2 0\n-4 2 -4 0\n-5 0\n1 5 0\n-4 1 -3 0\n\
+              -4 -2 -3 -2 0\n-3 -4 -1 0\n3 3 2 0",
+        )
+        .unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        let original = state.original_clauses().to_vec();
+        subsume(&mut state, original.clone(), original);
+        assert!(state.formula().clauses.len() < cnf.clauses.len());
+
+        let config = SolverConfig {
+            preprocess: true,
+            ..SolverConfig::default()
+        };
+        let model = solve_cdcl(&cnf, &config).into_assignment().unwrap();
+        assert!(check_assignment(&cnf, &model));
+    }
+
+    #[test]
+    fn test_probe_learns_units() {
+        // Both polarities of x1 imply x2, and x3 fails.
+        let cnf = parse_dimacs_str(b"p cnf 4 4\n-1 2 0\n1 2 0\n-3 4 0\n-3 -4 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        probe(&mut state, 0, usize::MAX, false);
+        assert_eq!(state.stats.probed_units, 2);
+        assert_eq!(state.decision_level, 0);
+        for lit in [packed_lit(2), packed_lit(-3)] {
+            assert_eq!(state.assignment.value(lit), Some(true));
+        }
+    }
+
+    #[test]
+    fn test_probe_adds_hyper_binary_resolvent() {
+        let cnf = parse_dimacs_str(b"p cnf 4 3\n-1 2 0\n-1 3 0\n-2 -3 4 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        assert_eq!(probe(&mut state, 0, usize::MAX, false), 0);
+        assert_eq!(state.stats.hyper_binary_resolvents, 1);
+        let implied = state
+            .binary_implications(packed_lit(1))
+            .iter()
+            .map(|implication| implication.lit)
+            .collect_vec();
+        assert!(implied.contains(&packed_lit(4)));
+        let proof = state.take_proof().unwrap();
+        assert!(matches!(
+            &proof[..],
+            [ProofStep::Add { hints, .. }] if *hints == [1, 2, 3]
+        ));
+    }
+
+    #[test]
+    fn test_probe_at_restart_learns_hyper_binary_resolvent() {
+        let cnf = parse_dimacs_str(b"p cnf 4 3\n-1 2 0\n-1 3 0\n-2 -3 4 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.seal_original_clauses();
+        probe(&mut state, 0, usize::MAX, true);
+        assert_eq!(state.original_clauses().len(), 3);
+        assert_eq!(state.num_learned_clauses(), 1);
+        state.delete_weak_learned_clauses(&DeletionStrategy::Lbd { max_lbd: 1 });
+        assert_eq!(state.num_learned_clauses(), 0);
+    }
+
+    #[test]
+    fn test_substitute_equivalent_literals() {
+        // x1 and x2 are equivalent, so the third clause becomes a duplicate
+        // of the fourth and the last one loses a literal.
+        let cnf =
+            parse_dimacs_str(b"p cnf 4 5\n-1 2 0\n1 -2 0\n-2 3 4 0\n-1 3 4 0\n1 2 -3 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        assert_eq!(substitute_equivalent_literals(&mut state), 1);
+        let clauses = state
+            .original_clauses()
+            .iter()
+            .map(|&cref| state.clauses.literals(cref).to_vec())
+            .collect_vec();
+        assert_eq!(
+            clauses,
+            [
+                vec![packed_lit(-1), packed_lit(3), packed_lit(4)],
+                vec![packed_lit(1), packed_lit(-3)]
+            ]
+        );
+
+        let mut assignment = Assignment::empty(4);
+        assignment.assign(packed_lit(1), 0);
+        state.reconstruction.extend(&mut assignment);
+        assert_eq!(assignment.value(packed_lit(2)), Some(true));
+    }
+
+    #[test]
+    fn test_literal_equivalent_to_its_negation() {
+        let cnf = parse_dimacs_str(b"p cnf 3 4\n-1 2 0\n-2 -1 0\n1 3 0\n-3 1 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        assert_eq!(substitute_equivalent_literals(&mut state), 0);
+        assert!(state.propagate().is_some());
+    }
+
+    #[test]
+    fn test_vivify_drops_implied_literal() {
+        // Falsifying x1 falsifies x2 through the first clause.
+        let cnf = parse_dimacs_str(b"p cnf 3 2\n1 -2 0\n1 2 3 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.enable_proof();
+        let candidates = state.original_clauses()[1..].to_vec();
+        assert_eq!(vivify(&mut state, candidates, usize::MAX), 1);
+        assert_eq!(state.decision_level, 0);
+        let clauses = state
+            .original_clauses()
+            .iter()
+            .map(|&cref| state.clauses.literals(cref).to_vec())
+            .collect_vec();
+        assert_eq!(
+            clauses,
+            [
+                vec![packed_lit(1), packed_lit(-2)],
+                vec![packed_lit(1), packed_lit(3)]
+            ]
+        );
+        let proof = state.take_proof().unwrap();
+        assert!(matches!(
+            &proof[0],
+            ProofStep::Add { hints, .. } if *hints == [1, 2]
+        ));
+    }
+
+    #[test]
+    fn test_elimination_is_bounded() {
+        // Every resolvent on x1 is distinct and non-tautological: 9 of them for 6 clauses.
+        let cnf =
+            parse_dimacs_str(b"p cnf 7 6\n1 2 0\n1 3 0\n1 4 0\n-1 5 0\n-1 6 0\n-1 7 0").unwrap();
+        let state = SolverState::from_cnf(&cnf);
+        let (pos, neg) = state.original_clauses().split_at(3);
+        assert_eq!(bounded_resolvents(&state, 0, pos, neg, 6), None);
+        let resolvents = bounded_resolvents(&state, 0, pos, neg, 9).unwrap();
+        assert_eq!(resolvents.len(), 9);
+        assert_eq!(resolvents[0].0, [packed_lit(2), packed_lit(5)]);
+    }
+
+    #[test]
+    fn test_reconstruction_flips_pivots_latest_first() {
+        let mut stack = ReconstructionStack::default();
+        stack.push(packed_lit(1), vec![packed_lit(1), packed_lit(2)]);
+        stack.push(packed_lit(-2), vec![packed_lit(-2), packed_lit(3)]);
+        // All false: (-2 3) is repaired first by x2 = false, which then
+        // leaves (1 2) for x1 = true.
+        let mut assignment = Assignment::empty(3);
+        for var_index in 0..3 {
+            assignment.assign(PackedLit::new(var_index, Val::False), 0);
+        }
+        stack.extend(&mut assignment);
+        assert_eq!(assignment.value(packed_lit(1)), Some(true));
+        assert_eq!(assignment.value(packed_lit(2)), Some(false));
+        assert_eq!(assignment.value(packed_lit(3)), Some(false));
+    }
+
+    #[test]
+    fn test_model_satisfies_original_formula() {
+        let cnf = parse_dimacs_str(
+            b"p cnf 6 7\n1 2 0\n-1 3 0\n-2 -3 0\n3 4 5 0\n-4 6 0\n-5 -6 0\n-3 -6 0",
+        )
+        .unwrap();
+        let config = SolverConfig {
+            preprocess: true,
+            ..SolverConfig::default()
+        };
+        let result = solve_cdcl(&cnf, &config);
+        let assignment = result.into_assignment().unwrap();
+        assert!(assignment.get_unassigned_var().is_none() && check_assignment(&cnf, &assignment));
+    }
+}
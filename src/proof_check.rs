@@ -0,0 +1,856 @@
+// Checkers for DRAT and LRAT refutations of a CNF formula.
+
+use std::collections::HashMap;
+use std::io;
+
+use itertools::Itertools;
+use log::{info, warn};
+
+use crate::formula::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Verified,
+    /// The proof step on this 1-based line (or step, for binary DRAT) is not justified.
+    Failed {
+        line: usize,
+        reason: String,
+    },
+    /// Every step checked out, but the proof never derives the empty clause.
+    Incomplete,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Verified => write!(f, "VERIFIED"),
+            Verdict::Failed { line, reason } => write!(f, "NOT VERIFIED: line {line}: {reason}"),
+            Verdict::Incomplete => {
+                write!(f, "NOT VERIFIED: proof does not derive the empty clause")
+            }
+        }
+    }
+}
+
+fn invalid_data(line: usize, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {line}: {message}"),
+    )
+}
+
+fn to_lit(num: i64) -> Lit {
+    Lit {
+        var: Var {
+            index: num.unsigned_abs() as usize,
+        },
+        value: if num > 0 { Val::True } else { Val::False },
+    }
+}
+
+// Index of a literal into per-literal tables such as watch lists.
+fn lit_code(lit: Lit) -> usize {
+    2 * lit.var.index + usize::from(lit.value == Val::False)
+}
+
+struct DratStep {
+    line: usize,
+    delete: bool,
+    literals: Vec<Lit>,
+}
+
+// Like drat-trim, treat the proof as binary if any of its first bytes is
+// neither printable nor whitespace.
+fn is_binary_drat(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .take(10)
+        .any(|&b| !(b.is_ascii_graphic() || b.is_ascii_whitespace()))
+}
+
+fn parse_drat_text(bytes: &[u8]) -> io::Result<Vec<DratStep>> {
+    let text = std::str::from_utf8(bytes).map_err(|e| invalid_data(0, e))?;
+    let mut steps = vec![];
+    let mut current: Option<DratStep> = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        if line.starts_with('c') {
+            continue;
+        }
+        for token in line.split_whitespace() {
+            let step = current.get_or_insert_with(|| DratStep {
+                line: line_idx + 1,
+                delete: false,
+                literals: vec![],
+            });
+            if token == "d" && step.literals.is_empty() && !step.delete {
+                step.delete = true;
+                continue;
+            }
+            let num = token
+                .parse::<i64>()
+                .map_err(|_| invalid_data(line_idx + 1, format!("invalid literal: {token}")))?;
+            if num == 0 {
+                steps.extend(current.take());
+            } else {
+                step.literals.push(to_lit(num));
+            }
+        }
+    }
+
+    if current.is_some() {
+        return Err(invalid_data(text.lines().count(), "unterminated clause"));
+    }
+    Ok(steps)
+}
+
+fn parse_drat_binary(bytes: &[u8]) -> io::Result<Vec<DratStep>> {
+    let mut steps = vec![];
+    let mut iter = bytes.iter().copied();
+
+    while let Some(tag) = iter.next() {
+        let line = steps.len() + 1;
+        let delete = match tag {
+            b'a' => false,
+            b'd' => true,
+            _ => return Err(invalid_data(line, format!("invalid step tag {tag:#x}"))),
+        };
+        let mut literals = vec![];
+        loop {
+            let mut encoded: u64 = 0;
+            let mut shift = 0;
+            loop {
+                let byte = iter
+                    .next()
+                    .ok_or_else(|| invalid_data(line, "unterminated clause"))?;
+                encoded |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            if encoded == 0 {
+                break;
+            }
+            let var = (encoded / 2) as i64;
+            literals.push(to_lit(if encoded.is_multiple_of(2) { var } else { -var }));
+        }
+        steps.push(DratStep {
+            line,
+            delete,
+            literals,
+        });
+    }
+    Ok(steps)
+}
+
+// Clause database with watched-literal propagation for reverse unit
+// propagation (RUP) checks. Every check starts from the empty assignment and
+// undoes itself, so the watches stay valid as clauses are toggled on and off.
+struct Checker {
+    clauses: Vec<Vec<Lit>>,
+    // The literal a lemma was written with first; RAT checks pivot on it.
+    pivots: Vec<Option<Lit>>,
+    active: Vec<bool>,
+    watched: Vec<bool>,
+    core: Vec<bool>,
+    watches: Vec<Vec<usize>>,
+    units: Vec<usize>,
+    values: Vec<Option<Val>>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+}
+
+impl Checker {
+    fn new(num_vars: usize) -> Self {
+        Self {
+            clauses: vec![],
+            pivots: vec![],
+            active: vec![],
+            watched: vec![],
+            core: vec![],
+            watches: vec![vec![]; 2 * (num_vars + 1)],
+            units: vec![],
+            values: vec![None; num_vars + 1],
+            reasons: vec![None; num_vars + 1],
+            trail: vec![],
+        }
+    }
+
+    fn add(&mut self, literals: &[Lit]) -> usize {
+        let literals = literals.iter().copied().unique().collect_vec();
+        self.pivots.push(literals.first().copied());
+        self.clauses.push(literals);
+        self.active.push(false);
+        self.watched.push(false);
+        self.core.push(false);
+        self.clauses.len() - 1
+    }
+
+    fn activate(&mut self, idx: usize) {
+        self.active[idx] = true;
+        if self.watched[idx] {
+            return;
+        }
+        self.watched[idx] = true;
+        match self.clauses[idx].as_slice() {
+            [] => {}
+            [_] => self.units.push(idx),
+            [a, b, ..] => {
+                self.watches[lit_code(*a)].push(idx);
+                self.watches[lit_code(*b)].push(idx);
+            }
+        }
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.var.index].map(|v| v == lit.value)
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.values[lit.var.index] = Some(lit.value);
+        self.reasons[lit.var.index] = reason;
+        self.trail.push(lit);
+    }
+
+    // Returns a falsified clause, if propagation reaches one.
+    fn propagate(&mut self) -> Option<usize> {
+        let mut head = 0;
+        while head < self.trail.len() {
+            let false_lit = self.trail[head].not();
+            head += 1;
+
+            let mut watch_list = std::mem::take(&mut self.watches[lit_code(false_lit)]);
+            let mut i = 0;
+            let mut conflict = None;
+            while i < watch_list.len() {
+                let idx = watch_list[i];
+                if !self.active[idx] {
+                    i += 1;
+                    continue;
+                }
+                let clause = &mut self.clauses[idx];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let other = clause[0];
+                if self.values[other.var.index] == Some(other.value) {
+                    i += 1;
+                    continue;
+                }
+                let replacement = (2..clause.len()).find(|&k| {
+                    let lit = clause[k];
+                    self.values[lit.var.index] != Some(lit.not().value)
+                });
+                if let Some(k) = replacement {
+                    clause.swap(1, k);
+                    let new_watch = clause[1];
+                    self.watches[lit_code(new_watch)].push(idx);
+                    watch_list.swap_remove(i);
+                } else if self.value(other) == Some(false) {
+                    conflict = Some(idx);
+                    break;
+                } else {
+                    self.assign(other, Some(idx));
+                    i += 1;
+                }
+            }
+            self.watches[lit_code(false_lit)] = watch_list;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    // Clauses the conflict depends on: the falsified clause and, transitively,
+    // the reasons of its literals.
+    fn analyze(&self, conflict: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.values.len()];
+        for lit in &self.clauses[conflict] {
+            seen[lit.var.index] = true;
+        }
+        let mut deps = vec![conflict];
+        for lit in self.trail.iter().rev() {
+            if !seen[lit.var.index] {
+                continue;
+            }
+            if let Some(reason) = self.reasons[lit.var.index] {
+                deps.push(reason);
+                for other in &self.clauses[reason] {
+                    seen[other.var.index] = true;
+                }
+            }
+        }
+        deps
+    }
+
+    fn backtrack(&mut self) {
+        for lit in self.trail.drain(..) {
+            self.values[lit.var.index] = None;
+            self.reasons[lit.var.index] = None;
+        }
+    }
+
+    // Reverse unit propagation: falsify `literals` and propagate. Returns the
+    // clauses used to reach a conflict, or None if there is no conflict.
+    fn rup(&mut self, literals: &[Lit]) -> Option<Vec<usize>> {
+        let mut deps = None;
+        for &lit in literals {
+            match self.value(lit) {
+                Some(true) => {
+                    // The clause contains both `lit` and its negation.
+                    deps = Some(vec![]);
+                    break;
+                }
+                Some(false) => {}
+                None => self.assign(lit.not(), None),
+            }
+        }
+
+        if deps.is_none() {
+            for i in 0..self.units.len() {
+                let idx = self.units[i];
+                if !self.active[idx] {
+                    continue;
+                }
+                let unit = self.clauses[idx][0];
+                match self.value(unit) {
+                    Some(true) => {}
+                    Some(false) => {
+                        deps = Some(self.analyze(idx));
+                        break;
+                    }
+                    None => self.assign(unit, Some(idx)),
+                }
+            }
+        }
+
+        if deps.is_none() {
+            deps = self.propagate().map(|conflict| self.analyze(conflict));
+        }
+        self.backtrack();
+        deps
+    }
+
+    // Check a lemma against the currently active clauses, first as RUP and
+    // then as RAT on its first literal. Marks every clause used as core.
+    fn check_lemma(&mut self, idx: usize) -> bool {
+        let lemma = self.clauses[idx].clone();
+        let deps = match self.rup(&lemma) {
+            Some(deps) => deps,
+            None => {
+                let Some(pivot) = self.pivots[idx] else {
+                    return false;
+                };
+                let candidates = (0..self.clauses.len())
+                    .filter(|&i| self.active[i] && self.clauses[i].contains(&pivot.not()))
+                    .collect_vec();
+                let mut deps = vec![];
+                for candidate in candidates {
+                    let resolvent = lemma
+                        .iter()
+                        .chain(
+                            self.clauses[candidate]
+                                .iter()
+                                .filter(|&&l| l != pivot.not()),
+                        )
+                        .copied()
+                        .collect_vec();
+                    match self.rup(&resolvent) {
+                        Some(candidate_deps) => {
+                            deps.extend(candidate_deps);
+                            deps.push(candidate);
+                        }
+                        None => return false,
+                    }
+                }
+                deps
+            }
+        };
+        for dep in deps {
+            self.core[dep] = true;
+        }
+        true
+    }
+}
+
+enum Event {
+    Added { idx: usize, line: usize },
+    Deleted { idx: usize },
+}
+
+/// Check a DRAT proof (text or binary, detected automatically) by backward
+/// checking: only lemmas that the empty clause transitively depends on are
+/// verified, each against the clause database as it stood when it was added.
+pub fn check_drat(cnf: &CnfFormula, mut reader: impl io::BufRead) -> io::Result<Verdict> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let steps = if is_binary_drat(&bytes) {
+        parse_drat_binary(&bytes)?
+    } else {
+        parse_drat_text(&bytes)?
+    };
+
+    let num_vars = steps
+        .iter()
+        .flat_map(|step| &step.literals)
+        .map(|lit| lit.var.index)
+        .fold(cnf.num_vars, usize::max);
+    let mut checker = Checker::new(num_vars);
+    let mut by_key: HashMap<Vec<Lit>, Vec<usize>> = HashMap::new();
+    let key = |literals: &[Lit]| literals.iter().copied().sorted().dedup().collect_vec();
+
+    for clause in &cnf.clauses {
+        let idx = checker.add(&clause.literals);
+        checker.activate(idx);
+        by_key.entry(key(&clause.literals)).or_default().push(idx);
+    }
+
+    // Forward pass: replay the proof up to the first empty clause.
+    let mut events = vec![];
+    let mut refuted = false;
+    for step in steps {
+        if step.delete {
+            let key = key(&step.literals);
+            if key.len() == 1 {
+                // As in drat-trim, unit deletions are ignored: they may still
+                // be needed to justify later lemmas by propagation.
+                info!("line {}: ignoring deletion of unit clause", step.line);
+                continue;
+            }
+            match by_key.get_mut(&key).and_then(Vec::pop) {
+                Some(idx) => {
+                    checker.active[idx] = false;
+                    events.push(Event::Deleted { idx });
+                }
+                None => warn!("line {}: ignoring deletion of unknown clause", step.line),
+            }
+        } else {
+            let idx = checker.add(&step.literals);
+            checker.activate(idx);
+            by_key.entry(key(&step.literals)).or_default().push(idx);
+            events.push(Event::Added {
+                idx,
+                line: step.line,
+            });
+            if step.literals.is_empty() {
+                checker.core[idx] = true;
+                refuted = true;
+                break;
+            }
+        }
+    }
+    if !refuted {
+        return Ok(Verdict::Incomplete);
+    }
+
+    // Backward pass: undo steps one by one, checking core lemmas as we go.
+    // Keep going after a failure so that the earliest failing lemma is reported.
+    let mut failure = None;
+    let mut checked = 0;
+    for event in events.iter().rev() {
+        match *event {
+            Event::Deleted { idx } => checker.activate(idx),
+            Event::Added { idx, line } => {
+                checker.active[idx] = false;
+                if !checker.core[idx] {
+                    continue;
+                }
+                checked += 1;
+                if !checker.check_lemma(idx) {
+                    failure = Some((line, idx));
+                }
+            }
+        }
+    }
+    info!("Checked {checked} core lemmas");
+
+    Ok(match failure {
+        Some((line, idx)) => Verdict::Failed {
+            line,
+            reason: format!(
+                "lemma {} is neither RUP nor RAT",
+                Clause {
+                    literals: checker.clauses[idx].clone()
+                }
+            ),
+        },
+        None => Verdict::Verified,
+    })
+}
+
+fn lrat_value(lit: &Lit, values: &[Option<Val>]) -> Option<bool> {
+    values
+        .get(lit.var.index)
+        .copied()
+        .flatten()
+        .map(|v| v == lit.value)
+}
+
+fn lrat_assign(lit: Lit, values: &mut Vec<Option<Val>>, assigned: &mut Vec<usize>) {
+    if values.len() <= lit.var.index {
+        values.resize(lit.var.index + 1, None);
+    }
+    values[lit.var.index] = Some(lit.value);
+    assigned.push(lit.var.index);
+}
+
+// Propagate through `hints` in order. Each must become unit under the
+// current assignment, until one is falsified outright. Returns whether one
+// was.
+fn replay_lrat_hints(
+    db: &HashMap<u64, Vec<Lit>>,
+    hints: &[i64],
+    values: &mut Vec<Option<Val>>,
+    assigned: &mut Vec<usize>,
+) -> Result<bool, String> {
+    for &hint in hints {
+        let Some(clause) = db.get(&(hint as u64)) else {
+            return Err(format!("hint {hint} refers to an unknown clause"));
+        };
+        if clause
+            .iter()
+            .any(|lit| lrat_value(lit, values) == Some(true))
+        {
+            return Err(format!("hint {hint} is already satisfied"));
+        }
+        let unassigned = clause
+            .iter()
+            .filter(|lit| lrat_value(lit, values).is_none())
+            .collect_vec();
+        match unassigned.as_slice() {
+            [] => return Ok(true),
+            [unit] => lrat_assign(**unit, values, assigned),
+            _ => return Err(format!("hint {hint} is not unit")),
+        }
+    }
+    Ok(false)
+}
+
+// Check one LRAT lemma: the hints up to the first negative one must lead to
+// a conflict under the negated lemma. Failing that, the lemma must be RAT on
+// its first literal.
+fn check_lrat_lemma(
+    db: &HashMap<u64, Vec<Lit>>,
+    literals: &[Lit],
+    hints: &[i64],
+    values: &mut Vec<Option<Val>>,
+) -> Result<(), String> {
+    if literals.iter().any(|lit| literals.contains(&lit.not())) {
+        return Ok(());
+    }
+    let mut assigned = vec![];
+    for lit in literals {
+        lrat_assign(lit.not(), values, &mut assigned);
+    }
+    let split = hints
+        .iter()
+        .position(|&hint| hint < 0)
+        .unwrap_or(hints.len());
+    let result = match replay_lrat_hints(db, &hints[..split], values, &mut assigned) {
+        Ok(true) => Ok(()),
+        Ok(false) => check_lrat_rat(db, literals.first(), &hints[split..], values),
+        Err(reason) => Err(reason),
+    };
+
+    for var_idx in assigned {
+        values[var_idx] = None;
+    }
+    result
+}
+
+// Every clause containing the negation of `pivot` must be named by a negative
+// hint, followed by hints that lead to a conflict under the negated resolvent
+// of the clause with the lemma, whose negation is already assigned.
+fn check_lrat_rat(
+    db: &HashMap<u64, Vec<Lit>>,
+    pivot: Option<&Lit>,
+    hints: &[i64],
+    values: &mut Vec<Option<Val>>,
+) -> Result<(), String> {
+    let Some(&pivot) = pivot else {
+        return Err("hints do not lead to a conflict".to_string());
+    };
+    let mut blocks = HashMap::new();
+    let mut rest = hints;
+    while let Some((&candidate, tail)) = rest.split_first() {
+        let end = tail.iter().position(|&hint| hint < 0).unwrap_or(tail.len());
+        blocks.insert(candidate.unsigned_abs(), &tail[..end]);
+        rest = &tail[end..];
+    }
+
+    let candidates = db
+        .iter()
+        .filter(|(_, clause)| clause.contains(&pivot.not()))
+        .sorted_by_key(|&(&id, _)| id);
+    for (&id, clause) in candidates {
+        let Some(block) = blocks.get(&id) else {
+            return Err(if blocks.is_empty() {
+                "hints do not lead to a conflict".to_string()
+            } else {
+                format!("no RAT hints for clause {id}")
+            });
+        };
+        let mut assigned = vec![];
+        let mut result = Ok(false);
+        for &lit in clause.iter().filter(|&&lit| lit != pivot.not()) {
+            match lrat_value(&lit, values) {
+                // The resolvent contains both this literal and its negation.
+                Some(true) => {
+                    result = Ok(true);
+                    break;
+                }
+                Some(false) => {}
+                None => lrat_assign(lit.not(), values, &mut assigned),
+            }
+        }
+        if result == Ok(false) {
+            result = replay_lrat_hints(db, block, values, &mut assigned);
+        }
+        for var_idx in assigned {
+            values[var_idx] = None;
+        }
+        match result {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(format!(
+                    "RAT hints for clause {id} do not lead to a conflict"
+                ))
+            }
+            Err(reason) => return Err(reason),
+        }
+    }
+    Ok(())
+}
+
+/// Check an LRAT proof in a single forward pass, replaying the hints of each
+/// lemma. Original clauses are numbered from 1 in the order they appear in `cnf`.
+pub fn check_lrat(cnf: &CnfFormula, reader: impl io::BufRead) -> io::Result<Verdict> {
+    let mut db: HashMap<u64, Vec<Lit>> = cnf
+        .clauses
+        .iter()
+        .enumerate()
+        .map(|(i, clause)| (i as u64 + 1, clause.literals.clone()))
+        .collect();
+    let mut values = vec![None; cnf.num_vars + 1];
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_no = line_idx + 1;
+        if line.starts_with('c') || line.trim().is_empty() {
+            continue;
+        }
+        let tokens = line.split_whitespace().collect_vec();
+        let parse = |token: &str| {
+            token
+                .parse::<i64>()
+                .map_err(|_| invalid_data(line_no, format!("invalid number: {token}")))
+        };
+        let id = parse(tokens[0])?;
+
+        if tokens.get(1) == Some(&"d") {
+            for token in &tokens[2..] {
+                match parse(token)? {
+                    0 => break,
+                    deleted => {
+                        db.remove(&(deleted as u64));
+                    }
+                }
+            }
+            continue;
+        }
+
+        let nums = tokens[1..]
+            .iter()
+            .map(|t| parse(t))
+            .collect::<io::Result<Vec<_>>>()?;
+        let Some(split) = nums.iter().position(|&n| n == 0) else {
+            return Err(invalid_data(line_no, "unterminated clause"));
+        };
+        if nums.last() != Some(&0) || split == nums.len() - 1 {
+            return Err(invalid_data(line_no, "unterminated hint list"));
+        }
+        let literals = nums[..split].iter().map(|&n| to_lit(n)).collect_vec();
+        let hints = &nums[split + 1..nums.len() - 1];
+
+        if let Err(reason) = check_lrat_lemma(&db, &literals, hints, &mut values) {
+            return Ok(Verdict::Failed {
+                line: line_no,
+                reason,
+            });
+        }
+        if literals.is_empty() {
+            return Ok(Verdict::Verified);
+        }
+        db.insert(id as u64, literals);
+    }
+
+    Ok(Verdict::Incomplete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::*;
+    use crate::parser::{output_binary_drat, output_drat, output_lrat, parse_dimacs_str};
+    use crate::solve_cdcl::solve_cdcl;
+    use crate::solver_state::ProofStep;
+
+    const EXAMPLE4: &[u8] = include_bytes!("../examples/example4.cnf");
+
+    fn solver_proof(
+        cnf: &CnfFormula,
+        minimization: ClauseMinimization,
+        preprocess: bool,
+        chronological_backtracking: bool,
+    ) -> Vec<ProofStep> {
+        let config = SolverConfig {
+            polarity: PolarityHeuristic::PhaseSaving,
+            restart: RestartStrategy::Luby { unit: 2 },
+            deletion: DeletionStrategy::Lbd { max_lbd: 2 },
+            minimization,
+            preprocess,
+            vivification_budget: 1_000,
+            chronological_backtracking,
+            chronological_threshold: 0,
+            proof: true,
+            ..SolverConfig::default()
+        };
+        solve_cdcl(cnf, &config).unsat_proof().unwrap().to_vec()
+    }
+
+    fn render(
+        write: fn(&mut io::BufWriter<Vec<u8>>, &[ProofStep]) -> io::Result<()>,
+        proof: &[ProofStep],
+    ) -> Vec<u8> {
+        let mut writer = io::BufWriter::new(Vec::new());
+        write(&mut writer, proof).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_solver_proofs_verify() {
+        let cnf = parse_dimacs_str(EXAMPLE4).unwrap();
+        for (minimization, preprocess, chronological) in [
+            (ClauseMinimization::None, false, false),
+            (ClauseMinimization::Recursive, false, false),
+            (ClauseMinimization::Recursive, true, false),
+            (ClauseMinimization::Recursive, false, true),
+        ] {
+            let proof = solver_proof(&cnf, minimization, preprocess, chronological);
+            for drat in [
+                render(output_drat, &proof),
+                render(output_binary_drat, &proof),
+            ] {
+                assert_eq!(
+                    check_drat(&cnf, drat.as_slice()).unwrap(),
+                    Verdict::Verified
+                );
+            }
+            let lrat = render(output_lrat, &proof);
+            assert_eq!(
+                check_lrat(&cnf, lrat.as_slice()).unwrap(),
+                Verdict::Verified
+            );
+        }
+    }
+
+    #[test]
+    fn test_drat_reports_bad_lemma_line() {
+        // Neither lemma follows from this satisfiable formula; both are core.
+        let cnf = parse_dimacs_str(b"p cnf 2 2\n1 2 0\n-1 -2 0\n").unwrap();
+        let verdict = check_drat(&cnf, &b"c comment\n1 0\n-1 0\n0\n"[..]).unwrap();
+        assert_eq!(
+            verdict,
+            Verdict::Failed {
+                line: 2,
+                reason: "lemma (x1) is neither RUP nor RAT".to_string()
+            }
+        );
+
+        let cnf = parse_dimacs_str(b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+        assert_eq!(
+            check_drat(&cnf, &b"1 0\n0\n"[..]).unwrap(),
+            Verdict::Verified
+        );
+    }
+
+    #[test]
+    fn test_drat_incomplete_without_empty_clause() {
+        let cnf = parse_dimacs_str(b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+        assert_eq!(
+            check_drat(&cnf, &b"1 0\n"[..]).unwrap(),
+            Verdict::Incomplete
+        );
+    }
+
+    #[test]
+    fn test_drat_rat_lemma() {
+        // x3 is fresh, so (x3 -1) is RAT on x3 though not RUP. Once x1 is
+        // refuted it is never needed, so check it directly.
+        let cnf = parse_dimacs_str(b"p cnf 3 2\n1 2 0\n-1 -2 0\n").unwrap();
+        let mut checker = Checker::new(3);
+        for clause in &cnf.clauses {
+            let idx = checker.add(&clause.literals);
+            checker.activate(idx);
+        }
+        let rat = checker.add(&[to_lit(3), to_lit(-1)]);
+        assert!(checker.check_lemma(rat));
+        // (x1) is RAT on x1 only if (x1 -2) is RUP, which it is not.
+        let not_rat = checker.add(&[to_lit(1)]);
+        assert!(!checker.check_lemma(not_rat));
+    }
+
+    #[test]
+    fn test_drat_deletion_is_respected() {
+        let cnf = parse_dimacs_str(b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+        // Without (1 -2), the lemma "1" is no longer RUP.
+        let verdict = check_drat(&cnf, &b"d -2 1 0\n1 0\n0\n"[..]).unwrap();
+        assert!(matches!(verdict, Verdict::Failed { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_lrat_rat_lemma() {
+        let cnf = parse_dimacs_str(b"p cnf 3 2\n1 2 0\n-1 -2 0\n").unwrap();
+        // x3 is fresh, so (3 -1) is RAT on x3 without any hints. Then (-3 -2)
+        // resolves with it into (-1 -2), which needs clause 2 as the hint
+        // for clause 3.
+        assert_eq!(
+            check_lrat(&cnf, &b"3 3 -1 0 0\n4 -3 -2 0 -3 2 0\n"[..]).unwrap(),
+            Verdict::Incomplete
+        );
+        assert_eq!(
+            check_lrat(&cnf, &b"3 3 -1 0 0\n4 -3 -2 0 0\n"[..]).unwrap(),
+            Verdict::Failed {
+                line: 2,
+                reason: "hints do not lead to a conflict".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_lrat_rejects_bad_hints() {
+        let cnf = parse_dimacs_str(b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+        assert_eq!(
+            check_lrat(&cnf, &b"5 1 0 1 3 0\n6 0 5 2 4 0\n"[..]).unwrap(),
+            Verdict::Verified
+        );
+        // Under -1, clause 2 (-1 2) is already satisfied.
+        assert_eq!(
+            check_lrat(&cnf, &b"5 1 0 2 1 3 0\n"[..]).unwrap(),
+            Verdict::Failed {
+                line: 1,
+                reason: "hint 2 is already satisfied".to_string()
+            }
+        );
+        // Deleted clauses can no longer be used as hints.
+        assert_eq!(
+            check_lrat(&cnf, &b"4 d 3 0\n5 1 0 1 3 0\n"[..]).unwrap(),
+            Verdict::Failed {
+                line: 2,
+                reason: "hint 3 refers to an unknown clause".to_string()
+            }
+        );
+    }
+}
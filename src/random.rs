@@ -1,20 +1,18 @@
 use crate::formula::*;
 use rand::prelude::*;
-use std::collections::HashSet;
 
-pub fn generate_random_cnf(n: usize, k: usize, l: usize) -> CnfFormula {
+pub fn generate_random_cnf(n: usize, k: usize, l: usize, rng: &mut impl Rng) -> CnfFormula {
     // Generates a random k-SAT CNF formula with n variables and l clauses
 
     if k > n {
         panic!("Cannot generate clauses with {k} variables when only {n} variables exist");
     }
 
-    let mut rng = rand::rng();
     let mut clauses = Vec::with_capacity(l);
 
     // Generate L random clauses
     for _ in 0..l {
-        clauses.push(generate_random_clause(n, k, &mut rng));
+        clauses.push(generate_random_clause(n, k, rng));
     }
 
     CnfFormula {
@@ -23,12 +21,16 @@ pub fn generate_random_cnf(n: usize, k: usize, l: usize) -> CnfFormula {
     }
 }
 
-fn generate_random_clause(n: usize, k: usize, rng: &mut ThreadRng) -> Clause {
-    // Choose k unique variables from 1..=n
-    let mut chosen_vars = HashSet::new();
+fn generate_random_clause(n: usize, k: usize, rng: &mut impl Rng) -> Clause {
+    // Choose k unique variables from 1..=n, in the order drawn so that the
+    // clause only depends on the generator
+    let mut chosen_vars = Vec::with_capacity(k);
 
     while chosen_vars.len() < k {
-        chosen_vars.insert(rng.random_range(1..=n));
+        let var_index = rng.random_range(1..=n);
+        if !chosen_vars.contains(&var_index) {
+            chosen_vars.push(var_index);
+        }
     }
 
     // Convert to literals with random negation
@@ -49,6 +51,11 @@ fn generate_random_clause(n: usize, k: usize, rng: &mut ThreadRng) -> Clause {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
+
+    fn generate_random_cnf(n: usize, k: usize, l: usize) -> CnfFormula {
+        super::generate_random_cnf(n, k, l, &mut StdRng::seed_from_u64(0))
+    }
 
     #[test]
     fn test_generate_random_cnf_basic() {
@@ -63,6 +70,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_same_seed_same_formula() {
+        let generate =
+            |seed| super::generate_random_cnf(20, 3, 50, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(generate(1), generate(1));
+        assert_ne!(generate(1), generate(2));
+    }
+
     #[test]
     fn test_clause_has_unique_variables() {
         let formula = generate_random_cnf(10, 4, 5);
@@ -0,0 +1,225 @@
+// Rephasing for `PolarityHeuristic::Target`: every so often, the saved and
+// target phases are reset, in turn, to the original phases, their inverse,
+// the best phases seen so far, random ones, or the result of a local search.
+
+use itertools::Itertools;
+use log::info;
+use rand::prelude::*;
+
+use crate::formula::*;
+use crate::solver_state::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phases {
+    Original,
+    Inverted,
+    Best,
+    Random,
+    Walk,
+}
+
+// As in CaDiCaL, every other rephasing goes back to the best phases.
+const CYCLE: [Phases; 8] = [
+    Phases::Best,
+    Phases::Walk,
+    Phases::Original,
+    Phases::Best,
+    Phases::Walk,
+    Phases::Inverted,
+    Phases::Best,
+    Phases::Random,
+];
+
+// Conflicts before the first rephasing, and added to the gap after each.
+const REPHASE_INTERVAL: u32 = 1000;
+// Flips a local search may make.
+const WALK_FLIPS: usize = 100_000;
+// Probability of flipping a random literal of a clause rather than the one
+// falsifying the fewest other clauses.
+const WALK_NOISE: f64 = 0.5;
+
+/// Tracks when to rephase, and what to rephase to next.
+pub struct Rephaser {
+    count: u32,
+    next_rephase: u32,
+    rng: StdRng,
+}
+
+impl Rephaser {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            count: 0,
+            next_rephase: REPHASE_INTERVAL,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn should_rephase(&self, conflict_count: u32) -> bool {
+        conflict_count >= self.next_rephase
+    }
+
+    /// Reset the phases of `state` to the next kind in the cycle.
+    pub fn rephase(&mut self, state: &mut SolverState) {
+        let kind = CYCLE[self.count as usize % CYCLE.len()];
+        info!("Rephase at conflict {} to {kind:?}", state.conflict_count);
+        let phases = match kind {
+            Phases::Original => vec![Val::False; state.num_vars],
+            Phases::Inverted => vec![Val::True; state.num_vars],
+            Phases::Best => state.take_best_phases(),
+            Phases::Random => (0..state.num_vars)
+                .map(|_| {
+                    if self.rng.random_bool(0.5) {
+                        Val::True
+                    } else {
+                        Val::False
+                    }
+                })
+                .collect(),
+            Phases::Walk => walk(state, WALK_FLIPS, &mut self.rng),
+        };
+        state.rephase(phases);
+        self.count += 1;
+        self.next_rephase = state
+            .conflict_count
+            .saturating_add(REPHASE_INTERVAL * (self.count + 1));
+    }
+}
+
+impl Default for Rephaser {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// WalkSAT over the original clauses, starting from the saved phases, with
+/// every variable assigned at decision level 0 held fixed. Returns the phases
+/// of the assignment that falsified the fewest clauses.
+pub fn walk(state: &SolverState, flips: usize, rng: &mut StdRng) -> Vec<Val> {
+    let fixed = |lit: PackedLit| state.assignment.level(lit) == Some(0);
+    let mut values = (1..=state.num_vars)
+        .map(|index| state.get_phase(Var { index }))
+        .collect_vec();
+    for element in &state.trail {
+        if fixed(element.lit) {
+            values[element.lit.var_index()] = element.lit.value();
+        }
+    }
+    let clauses = state
+        .original_clauses()
+        .iter()
+        .filter(|&&cref| {
+            !state
+                .clauses
+                .literals(cref)
+                .iter()
+                .any(|&lit| fixed(lit) && state.assignment.value(lit) == Some(true))
+        })
+        .map(|&cref| {
+            state
+                .clauses
+                .literals(cref)
+                .iter()
+                .copied()
+                .filter(|&lit| !fixed(lit))
+                .collect_vec()
+        })
+        .filter(|literals| !literals.is_empty())
+        .collect_vec();
+
+    let mut occurrences = vec![vec![]; state.num_vars * 2];
+    for (c, literals) in clauses.iter().enumerate() {
+        for lit in literals {
+            occurrences[lit.index()].push(c);
+        }
+    }
+    let is_true = |values: &[Val], lit: PackedLit| values[lit.var_index()] == lit.value();
+    let mut true_counts = clauses
+        .iter()
+        .map(|literals| {
+            literals
+                .iter()
+                .filter(|&&lit| is_true(&values, lit))
+                .count()
+        })
+        .collect_vec();
+    // Falsified clauses, and the position of each in that list.
+    let mut falsified = vec![];
+    let mut position = vec![usize::MAX; clauses.len()];
+    for c in 0..clauses.len() {
+        if true_counts[c] == 0 {
+            position[c] = falsified.len();
+            falsified.push(c);
+        }
+    }
+
+    let mut best = values.clone();
+    let mut best_falsified = falsified.len();
+    for _ in 0..flips {
+        if falsified.is_empty() {
+            break;
+        }
+        let literals = &clauses[falsified[rng.random_range(0..falsified.len())]];
+        // Clauses that making `lit` true would falsify.
+        let breaks = |lit: PackedLit| {
+            occurrences[lit.not().index()]
+                .iter()
+                .filter(|&&d| true_counts[d] == 1)
+                .count()
+        };
+        let lit = match literals.iter().copied().find(|&lit| breaks(lit) == 0) {
+            Some(lit) => lit,
+            None if rng.random_bool(WALK_NOISE) => literals[rng.random_range(0..literals.len())],
+            None => literals
+                .iter()
+                .copied()
+                .min_by_key(|&lit| breaks(lit))
+                .unwrap(),
+        };
+
+        values[lit.var_index()] = lit.value();
+        for &c in &occurrences[lit.index()] {
+            true_counts[c] += 1;
+            if true_counts[c] == 1 {
+                let last = falsified.pop().unwrap();
+                if last != c {
+                    falsified[position[c]] = last;
+                    position[last] = position[c];
+                }
+                position[c] = usize::MAX;
+            }
+        }
+        for &c in &occurrences[lit.not().index()] {
+            true_counts[c] -= 1;
+            if true_counts[c] == 0 {
+                position[c] = falsified.len();
+                falsified.push(c);
+            }
+        }
+        if falsified.len() < best_falsified {
+            best_falsified = falsified.len();
+            best.clone_from(&values);
+        }
+    }
+    info!("Local search left {best_falsified} clauses falsified");
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_dimacs_str;
+
+    #[test]
+    fn test_walk_finds_model() {
+        // The saved phases, all false, falsify three of the clauses.
+        let cnf = parse_dimacs_str(b"p cnf 4 4\n1 2 0\n3 4 0\n1 -2 3 0\n2 4 0").unwrap();
+        let state = SolverState::from_cnf(&cnf);
+        let mut rng = StdRng::seed_from_u64(0);
+        let phases = walk(&state, 1000, &mut rng);
+        let mut assignment = Assignment::empty(4);
+        for (index, value) in (1..=4).zip(phases) {
+            assignment.set(Var { index }, value, 0);
+        }
+        assert!(check_assignment(&cnf, &assignment));
+    }
+}
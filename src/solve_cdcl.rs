@@ -1,24 +1,34 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 
 use itertools::Itertools;
 use log::info;
 
 use crate::config::*;
 use crate::formula::*;
+use crate::preprocess;
+use crate::rephase::Rephaser;
 use crate::solver_state::*;
 
 struct ConflictingLits<'a> {
-    literals: BTreeSet<(u32, Lit)>,
+    literals: BTreeSet<(u32, PackedLit)>,
+    falsified_id: ClauseId,
+    // Trail position and ID of every reason used to derive the learned clause.
+    antecedents: Vec<(usize, ClauseId)>,
+    // The falsified clause and every reason resolved with it.
+    resolved: Vec<ClauseRef>,
     state: &'a SolverState,
 }
 
 impl<'a> ConflictingLits<'a> {
-    fn new(falsified_clause: Clause, state: &'a SolverState) -> Self {
+    fn new(falsified: ClauseRef, state: &'a SolverState) -> Self {
         let mut ret = Self {
             literals: BTreeSet::new(),
+            falsified_id: state.clauses.id(falsified),
+            antecedents: Vec::new(),
+            resolved: vec![falsified],
             state,
         };
-        for lit in &falsified_clause.literals {
+        for lit in state.clauses.literals(falsified) {
             ret.insert(lit.not());
         }
         ret
@@ -35,43 +45,130 @@ impl<'a> ConflictingLits<'a> {
         decision_levels.next_back().unwrap_or(0)
     }
 
-    fn get_learned_clause(&self) -> Clause {
-        Clause {
-            literals: self.literals.iter().map(|(_, lit)| lit.not()).collect(),
+    fn get_learned_clause(&self) -> Vec<PackedLit> {
+        self.literals.iter().map(|(_, lit)| lit.not()).collect()
+    }
+
+    // Every reason only mentions literals assigned before the one it implies,
+    // so replaying them in trail order is a chain unit propagation can follow.
+    fn get_hints(&self) -> Vec<ClauseId> {
+        self.antecedents
+            .iter()
+            .sorted()
+            .dedup()
+            .map(|(_, id)| *id)
+            .chain([self.falsified_id])
+            .collect()
+    }
+
+    // Recursive minimization (MiniSat's deep mode): drop each literal whose
+    // negation follows from the rest of the clause through a chain of reasons
+    // at levels already in the clause. Returns the number of literals removed.
+    fn minimize(&mut self) -> usize {
+        let trail = &self.state.trail;
+        let mut trail_pos = vec![None; self.state.num_vars];
+        for (pos, trail_element) in trail.iter().enumerate() {
+            trail_pos[trail_element.lit.var_index()] = Some(pos);
+        }
+
+        let levels: HashSet<u32> = self.literals.iter().map(|(level, _)| *level).collect();
+        let mut marked = vec![false; self.state.num_vars];
+        for (_, lit) in &self.literals {
+            marked[lit.var_index()] = true;
+        }
+
+        let candidates: Vec<(u32, PackedLit)> = self.literals.iter().copied().collect();
+        let mut removed = 0;
+        for (level, lit) in candidates {
+            // Keep the asserting literal.
+            if level == self.state.decision_level {
+                continue;
+            }
+            if self.is_redundant(lit.var_index(), &trail_pos, &levels, &mut marked) {
+                self.literals.remove(&(level, lit));
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    fn is_redundant(
+        &mut self,
+        var_index: usize,
+        trail_pos: &[Option<usize>],
+        levels: &HashSet<u32>,
+        marked: &mut [bool],
+    ) -> bool {
+        let trail = &self.state.trail;
+        if let TrailReason::Decision = trail[trail_pos[var_index].unwrap()].reason {
+            return false;
+        }
+
+        let mut stack = vec![var_index];
+        let mut newly_marked = Vec::new();
+        let mut used = Vec::new();
+        while let Some(var_index) = stack.pop() {
+            let pos = trail_pos[var_index].unwrap();
+            let cref = trail[pos].reason.cref().unwrap();
+            used.push((pos, self.state.clauses.id(cref)));
+
+            for lit in self.state.reason_literals(&trail[pos]) {
+                if marked[lit.var_index()] {
+                    continue;
+                }
+                let level = self.state.assignment.level(lit).unwrap();
+                let pos = trail_pos[lit.var_index()].unwrap();
+                // Level-0 literals are followed too, so the proof hints cover them.
+                if trail[pos].reason != TrailReason::Decision
+                    && (level == 0 || levels.contains(&level))
+                {
+                    marked[lit.var_index()] = true;
+                    newly_marked.push(lit.var_index());
+                    stack.push(lit.var_index());
+                } else {
+                    for var_index in newly_marked {
+                        marked[var_index] = false;
+                    }
+                    return false;
+                }
+            }
         }
+
+        self.antecedents.extend(used);
+        true
     }
 
-    fn contains(&self, lit: Lit) -> bool {
-        let level = self.state.assignment.get_decision_level(&lit).unwrap();
+    fn contains(&self, lit: PackedLit) -> bool {
+        let level = self.state.assignment.level(lit).unwrap();
         self.literals.contains(&(level, lit))
     }
 
-    fn insert(&mut self, lit: Lit) {
+    fn insert(&mut self, lit: PackedLit) {
         self.literals
-            .insert((self.state.assignment.get_decision_level(&lit).unwrap(), lit));
+            .insert((self.state.assignment.level(lit).unwrap(), lit));
     }
 
-    fn remove(&mut self, lit: Lit) {
+    fn remove(&mut self, lit: PackedLit) {
         self.literals
-            .remove(&(self.state.assignment.get_decision_level(&lit).unwrap(), lit));
+            .remove(&(self.state.assignment.level(lit).unwrap(), lit));
     }
 
-    fn update(&mut self, trail_element: &TrailElement) {
-        let TrailReason::UnitProp(clause) = &trail_element.reason else {
-            // We should never be moving the UIP cut behind the last decision level.
-            unreachable!();
-        };
+    fn update(&mut self, pos: usize, trail_element: &TrailElement) {
+        // We should never be moving the UIP cut behind the last decision level.
+        let cref = trail_element.reason.cref().unwrap();
+        let state = self.state;
 
-        info!("\tTrail element: {trail_element} from {clause}");
+        info!(
+            "\tTrail element: {trail_element} from {}",
+            state.clauses.clause(cref)
+        );
 
-        for lit in clause.literals.iter() {
-            if lit.var == trail_element.lit.var {
-                assert!(lit.value == trail_element.lit.value);
-            } else {
-                self.insert(lit.not());
-            }
+        for lit in state.reason_literals(trail_element) {
+            self.insert(lit.not());
         }
         self.remove(trail_element.lit);
+        self.antecedents.push((pos, state.clauses.id(cref)));
+        self.resolved.push(cref);
     }
 }
 
@@ -88,30 +185,104 @@ impl std::fmt::Display for ConflictingLits<'_> {
     }
 }
 
-pub fn solve_cdcl_from_state(mut state: SolverState, config: &SolverConfig) -> SolverResult {
-    info!("Initial formula: {}", state.formula);
+/// Outcome of one call to `search`, before it is packaged into a `SolverResult`.
+enum SearchOutcome {
+    Satisfiable,
+    Unsatisfiable,
+    FailedAssumptions(Vec<Lit>),
+}
 
-    let mut scheduler = RestartScheduler::new(config.restart);
+// Collect the assumptions responsible for `failed` being falsified, in the
+// manner of MiniSat's analyzeFinal: walk the trail backwards from the end,
+// expanding the reasons of every marked literal. Every decision reached this
+// way is an assumption, since free decisions are only made once all
+// assumptions hold.
+fn analyze_final(state: &SolverState, failed: PackedLit) -> Vec<Lit> {
+    let mut seen = vec![false; state.num_vars];
+    seen[failed.var_index()] = true;
+    let mut core = vec![failed.into()];
+
+    for trail_element in state.trail.iter().rev() {
+        if !seen[trail_element.lit.var_index()] {
+            continue;
+        }
+        if trail_element.reason == TrailReason::Decision {
+            core.push(trail_element.lit.into());
+            continue;
+        }
+        for lit in state.reason_literals(trail_element) {
+            if state.assignment.level(lit) != Some(0) {
+                seen[lit.var_index()] = true;
+            }
+        }
+    }
+    core
+}
 
+// Antecedents of the empty clause once `falsified` is falsified at decision
+// level 0: every reason the conflict transitively depends on, in trail order,
+// followed by the falsified clause itself.
+fn refutation_hints(state: &SolverState, falsified: ClauseRef) -> Vec<ClauseId> {
+    let mut hints = state.implication_hints(state.clauses.literals(falsified));
+    hints.push(state.clauses.id(falsified));
+    hints
+}
+
+fn search(
+    state: &mut SolverState,
+    config: &SolverConfig,
+    scheduler: &mut RestartScheduler,
+    rephaser: &mut Rephaser,
+    assumptions: &[Lit],
+) -> SearchOutcome {
+    let assumptions = assumptions.iter().map(|&lit| lit.into()).collect_vec();
+    // Learned clauses with smaller IDs have been used to subsume others.
+    let mut subsumed_before = 0;
+    let mut restarts = 0;
+    // Probing at restarts resumes with this variable.
+    let mut next_probe = 0;
+    // Learned clauses with smaller IDs have had their turn at vivification.
+    let mut vivified_before = 0;
     loop {
-        match state.get_status() {
+        let status = state.get_status();
+
+        if matches!(status, Status::Satisfied | Status::UnassignedDecision(_)) {
+            // Assumptions are decided in order before any free decision is made.
+            if let Some(&lit) = assumptions
+                .iter()
+                .find(|&&lit| state.assignment.value(lit) != Some(true))
+            {
+                if state.assignment.value(lit) == Some(false) {
+                    info!("Assumption {lit} is falsified");
+                    return SearchOutcome::FailedAssumptions(analyze_final(state, lit));
+                }
+                info!("Assume: {lit}");
+                state.decide(lit.var(), lit.value());
+                continue;
+            }
+        }
+
+        match status {
             Status::Satisfied => {
-                return SolverResult::Satisfiable(state.assignment.fill_unassigned());
+                return SearchOutcome::Satisfiable;
             }
             Status::UnassignedDecision(var) => {
-                let value = match config.polarity {
-                    PolarityHeuristic::AlwaysFalse => Val::False,
-                    PolarityHeuristic::AlwaysTrue => Val::True,
-                    PolarityHeuristic::PhaseSaving => state.get_phase(var),
+                let var = state
+                    .random_decision_var(config.random_var_frequency)
+                    .unwrap_or(var);
+                let value = match state.random_polarity(config.random_polarity_frequency) {
+                    Some(value) => value,
+                    None => match config.polarity {
+                        PolarityHeuristic::AlwaysFalse => Val::False,
+                        PolarityHeuristic::AlwaysTrue => Val::True,
+                        PolarityHeuristic::PhaseSaving => state.get_phase(var),
+                        PolarityHeuristic::Target => state.get_target_phase(var),
+                    },
                 };
                 info!("Guess: {}", Lit { var, value });
                 state.decide(var, value);
             }
-            Status::UnassignedUnit(lit, clause) => {
-                info!("Unit: {lit} from {clause}");
-                state.assign_unitprop(lit.var, lit.value, clause);
-            }
-            Status::Falsified(falsified_clause) => {
+            Status::Falsified(falsified) => {
                 // We start with the cut placed after all unit propagations,
                 // and incrementally move it backwards until the ensuing
                 // learned clause would contain exactly one literal from
@@ -119,29 +290,56 @@ pub fn solve_cdcl_from_state(mut state: SolverState, config: &SolverConfig) -> S
 
                 info!(
                     "Falsified {} at trail: {}",
-                    falsified_clause,
+                    state.clauses.clause(falsified),
                     state.trail.iter().join(" ")
                 );
 
+                if config.chronological_backtracking {
+                    // The conflict may lie below the current level, and is
+                    // analyzed at its own.
+                    let (conflict_level, count) = state.conflict_level(falsified);
+                    state.backjump_to_decision_level(conflict_level);
+                    if count == 1 && conflict_level > 0 {
+                        // A missed implication rather than a conflict.
+                        let lit = state
+                            .clauses
+                            .literals(falsified)
+                            .iter()
+                            .copied()
+                            .find(|&lit| state.assignment.level(lit) == Some(conflict_level))
+                            .unwrap();
+                        state.backjump_to_decision_level(conflict_level - 1);
+                        state.assign_unitprop(lit, falsified);
+                        continue;
+                    }
+                }
+
                 if state.decision_level == 0 {
-                    return SolverResult::UnsatisfiableWithProof(state.formula.clauses);
+                    let hints = refutation_hints(state, falsified);
+                    state.conclude_proof(hints);
+                    return SearchOutcome::Unsatisfiable;
                 }
 
-                let mut conflict = ConflictingLits::new(falsified_clause, &state);
+                if config.polarity == PolarityHeuristic::Target {
+                    state.update_target_phases();
+                }
+                let mut conflict = ConflictingLits::new(falsified, state);
 
-                for trail_element in state.trail.iter().rev() {
+                for (pos, trail_element) in state.trail.iter().enumerate().rev() {
                     info!("\tContradiction: {conflict}");
-                    let backjump_level = conflict.get_backjump_level();
-                    if backjump_level != state.decision_level {
+                    if conflict.get_backjump_level() != state.decision_level {
                         // 1-UIP found: exactly one literal remains at the current level.
+                        let minimized = match config.minimization {
+                            ClauseMinimization::None => 0,
+                            ClauseMinimization::Recursive => conflict.minimize(),
+                        };
+                        let backjump_level = conflict.get_backjump_level();
                         let learned_clause = conflict.get_learned_clause();
                         debug_assert_eq!(
                             learned_clause
-                                .literals
                                 .iter()
-                                .filter(|lit| {
-                                    state.assignment.get_decision_level(lit)
-                                        == Some(state.decision_level)
+                                .filter(|&&lit| {
+                                    state.assignment.level(lit) == Some(state.decision_level)
                                 })
                                 .count(),
                             1,
@@ -149,33 +347,105 @@ pub fn solve_cdcl_from_state(mut state: SolverState, config: &SolverConfig) -> S
                              at the current decision level"
                         );
                         info!(
-                            "\tBackjumping from level {} to level {}, learning clause {}",
-                            state.decision_level, backjump_level, learned_clause
+                            "\tBackjumping from level {} to level {}, learning clause ({})",
+                            state.decision_level,
+                            backjump_level,
+                            learned_clause.iter().join(" V ")
                         );
+                        let hints = conflict.get_hints();
+                        let resolved = conflict.resolved;
+                        state.stats.minimized_literals += minimized as u64;
                         state.bump_var_activity(&learned_clause);
-                        state.learn_clause_with_meta(learned_clause);
-                        state.backjump_to_decision_level(backjump_level);
+                        state.bump_clause_activity(&resolved);
+                        scheduler
+                            .record_conflict(state.compute_lbd(&learned_clause), state.trail.len());
+                        let cref = state.learn_clause_with_meta(learned_clause, hints);
+                        if config.chronological_backtracking
+                            && state.decision_level - backjump_level
+                                > config.chronological_threshold
+                        {
+                            state.stats.chronological_backtracks += 1;
+                            state.backjump_to_decision_level(state.decision_level - 1);
+                        } else {
+                            state.backjump_to_decision_level(backjump_level);
+                        }
+                        // The learned clause is now unit on its first literal.
+                        let asserting = state.clauses.literals(cref)[0];
+                        state.assign_unitprop(asserting, cref);
                         state.conflict_count += 1;
 
                         if scheduler.should_restart(state.conflict_count) {
                             info!(
                                 "Restart at conflict {}, {} learned clauses",
                                 state.conflict_count,
-                                state.formula.clauses.len()
+                                state.num_learned_clauses()
                             );
-                            state.restart();
+                            restarts += 1;
+                            let due = |interval| interval > 0 && restarts % interval == 0;
+                            let subsume = due(config.subsumption_interval);
+                            let probe = config.probe_budget > 0 && due(config.probe_interval);
+                            let vivify =
+                                config.vivification_budget > 0 && due(config.vivification_interval);
+                            // Simplification needs level 0, so the trail is
+                            // only reused on restarts without it.
+                            let simplify = subsume || probe || vivify;
+                            state.restart(simplify);
                             state.delete_weak_learned_clauses(&config.deletion);
+                            if simplify {
+                                if subsume {
+                                    // Older clauses have already had their turn.
+                                    let learned = state.learned_clauses().collect_vec();
+                                    let recent = learned
+                                        .iter()
+                                        .copied()
+                                        .filter(|&cref| state.clauses.id(cref) >= subsumed_before)
+                                        .collect_vec();
+                                    if !recent.is_empty() {
+                                        subsumed_before = state.next_clause_id();
+                                        preprocess::subsume(state, recent, learned);
+                                    }
+                                }
+                                if probe {
+                                    next_probe = preprocess::probe(
+                                        state,
+                                        next_probe,
+                                        config.probe_budget as usize,
+                                        true,
+                                    );
+                                }
+                                if vivify {
+                                    let candidates = state
+                                        .learned_clauses()
+                                        .filter(|&cref| state.clauses.id(cref) >= vivified_before)
+                                        .collect();
+                                    vivified_before = state.next_clause_id();
+                                    preprocess::vivify(
+                                        state,
+                                        candidates,
+                                        config.vivification_budget as usize,
+                                    );
+                                }
+                            }
                             scheduler.advance(state.conflict_count);
                         }
+                        if config.polarity == PolarityHeuristic::Target
+                            && rephaser.should_rephase(state.conflict_count)
+                        {
+                            rephaser.rephase(state);
+                        }
 
                         break;
                     }
 
                     // Only expand trail elements whose literal is in the conflict set.
                     // Expanding unrelated literals would add spurious antecedents to the
-                    // learned clause, producing a weaker-than-1-UIP result.
-                    if conflict.contains(trail_element.lit) {
-                        conflict.update(trail_element);
+                    // learned clause, producing a weaker-than-1-UIP result. Literals
+                    // from lower levels can be interleaved after chronological
+                    // backtracking, and stay in the clause.
+                    if state.assignment.level(trail_element.lit) == Some(state.decision_level)
+                        && conflict.contains(trail_element.lit)
+                    {
+                        conflict.update(pos, trail_element);
                     }
                 }
             }
@@ -183,11 +453,160 @@ pub fn solve_cdcl_from_state(mut state: SolverState, config: &SolverConfig) -> S
     }
 }
 
+pub fn solve_cdcl_from_state(state: SolverState, config: &SolverConfig) -> SolverResult {
+    solve_cdcl_from_state_with_stats(state, config).0
+}
+
+fn solve_cdcl_from_state_with_stats(
+    mut state: SolverState,
+    config: &SolverConfig,
+) -> (SolverResult, SolverStats) {
+    info!("Initial formula: {}", state.formula());
+    state.configure_search(config);
+
+    let mut scheduler = RestartScheduler::new(config.restart);
+
+    let outcome = search(
+        &mut state,
+        config,
+        &mut scheduler,
+        &mut Rephaser::new(config.seed),
+        &[],
+    );
+    let stats = &state.stats;
+    info!(
+        "{} conflicts, {} chronological backtracks, {} levels reused on restart",
+        state.conflict_count, stats.chronological_backtracks, stats.reused_levels
+    );
+    info!(
+        "Minimization removed {} literals, vivification removed {}",
+        stats.minimized_literals, stats.vivified_literals
+    );
+    info!(
+        "Subsumption removed {} clauses and strengthened {}",
+        stats.subsumed_clauses, stats.strengthened_clauses
+    );
+    info!(
+        "Probing learned {} units and {} hyper-binary resolvents",
+        stats.probed_units, stats.hyper_binary_resolvents
+    );
+    info!(
+        "{} variables substituted, {} clauses eliminated, {} variables added",
+        stats.substituted_vars, stats.eliminated_clauses, stats.added_vars
+    );
+
+    let result = match outcome {
+        SearchOutcome::Satisfiable => SolverResult::Satisfiable(state.model()),
+        SearchOutcome::Unsatisfiable => match state.take_proof() {
+            Some(proof) => SolverResult::UnsatisfiableWithProof(proof),
+            None => SolverResult::Unsatisfiable,
+        },
+        SearchOutcome::FailedAssumptions(_) => unreachable!(),
+    };
+    (result, state.stats)
+}
+
 pub fn solve_cdcl(cnf: &CnfFormula, config: &SolverConfig) -> SolverResult {
+    solve_cdcl_with_stats(cnf, config).0
+}
+
+/// Like `solve_cdcl`, but also returns the counters accumulated while
+/// simplifying and searching.
+pub fn solve_cdcl_with_stats(
+    cnf: &CnfFormula,
+    config: &SolverConfig,
+) -> (SolverResult, SolverStats) {
     let mut state = SolverState::from_cnf(cnf);
+    if config.proof {
+        state.enable_proof();
+    }
+    if config.preprocess {
+        preprocess::preprocess(&mut state);
+    }
     state.pure_literal_eliminate();
     state.seal_original_clauses();
-    solve_cdcl_from_state(state, config)
+    solve_cdcl_from_state_with_stats(state, config)
+}
+
+/// A CDCL solver that can be queried repeatedly under different assumptions.
+///
+/// Learned clauses, variable activities and saved phases carry over between
+/// calls. Pure literal elimination is not applied, since a literal that is
+/// pure in one query need not be pure under the next set of assumptions.
+pub struct CdclSolver {
+    state: SolverState,
+    config: SolverConfig,
+    scheduler: RestartScheduler,
+    rephaser: Rephaser,
+    // Cleared once the formula is found UNSAT without any assumptions.
+    ok: bool,
+}
+
+impl CdclSolver {
+    pub fn new(cnf: &CnfFormula, config: &SolverConfig) -> Self {
+        let mut state = SolverState::from_cnf(cnf);
+        state.seal_original_clauses();
+        state.configure_search(config);
+        Self {
+            state,
+            config: *config,
+            scheduler: RestartScheduler::new(config.restart),
+            rephaser: Rephaser::new(config.seed),
+            ok: true,
+        }
+    }
+
+    pub fn state(&self) -> &SolverState {
+        &self.state
+    }
+
+    pub fn new_var(&mut self) -> Var {
+        self.state.new_var()
+    }
+
+    /// Add a permanent clause between queries. Returns false once the formula
+    /// is known to be UNSAT.
+    pub fn add_clause(&mut self, clause: Clause) -> bool {
+        self.ok &= self.state.add_clause(clause);
+        self.ok
+    }
+
+    pub fn solve(&mut self) -> SolverResult {
+        self.solve_with_assumptions(&[])
+    }
+
+    /// Solve the formula with every literal in `assumptions` forced true.
+    ///
+    /// Returns `UnsatisfiableUnderAssumptions` with the subset of assumptions
+    /// that led to the conflict, or plain `Unsatisfiable` once the formula is
+    /// refuted without any of them. Level-0 propagation is checked before the
+    /// first assumption is made, but a formula that is UNSAT only by search
+    /// may still report a core, e.g. when an assumption is false at level 0.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolverResult {
+        if !self.ok {
+            return SolverResult::Unsatisfiable;
+        }
+
+        let outcome = search(
+            &mut self.state,
+            &self.config,
+            &mut self.scheduler,
+            &mut self.rephaser,
+            assumptions,
+        );
+        let result = match outcome {
+            SearchOutcome::Satisfiable => SolverResult::Satisfiable(self.state.model()),
+            SearchOutcome::Unsatisfiable => {
+                self.ok = false;
+                SolverResult::Unsatisfiable
+            }
+            SearchOutcome::FailedAssumptions(core) => {
+                SolverResult::UnsatisfiableUnderAssumptions(core)
+            }
+        };
+        self.state.backjump_to_decision_level(0);
+        result
+    }
 }
 
 #[cfg(test)]
@@ -233,19 +652,7 @@ mod tests {
     fn test_vsids_prefers_recently_conflicting_var() {
         let cnf = parse_dimacs_str(b"\np cnf 4 4\n1 2 0\n3 4 0\n-1 -2 0\n-3 -4 0").unwrap();
         let mut state = SolverState::from_cnf(&cnf);
-        let clause = Clause {
-            literals: vec![
-                Lit {
-                    var: Var { index: 3 },
-                    value: Val::True,
-                },
-                Lit {
-                    var: Var { index: 4 },
-                    value: Val::True,
-                },
-            ],
-        };
-        state.bump_var_activity(&clause);
+        state.bump_var_activity(&[PackedLit::new(2, Val::True), PackedLit::new(3, Val::True)]);
         let decision = state.next_decision_var().unwrap();
         assert!(decision.index == 3 || decision.index == 4);
     }
@@ -257,11 +664,13 @@ mod tests {
             PolarityHeuristic::AlwaysFalse,
             PolarityHeuristic::AlwaysTrue,
             PolarityHeuristic::PhaseSaving,
+            PolarityHeuristic::Target,
         ] {
             let config = SolverConfig {
                 polarity,
                 restart: RestartStrategy::None,
                 deletion: DeletionStrategy::None,
+                ..SolverConfig::default()
             };
             let result = solve_cdcl(&cnf, &config);
             assert!(
@@ -305,10 +714,94 @@ mod tests {
             polarity: PolarityHeuristic::AlwaysFalse,
             restart: RestartStrategy::Luby { unit: 5 },
             deletion: DeletionStrategy::None,
+            ..SolverConfig::default()
         };
         assert!(!solve_cdcl(&cnf, &config).is_satisfiable());
     }
 
+    #[test]
+    fn test_branching_heuristics_agree() {
+        let unsat = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let sat = parse_dimacs_str(b"\np cnf 5 4\n1 2 0\n1 -2 0\n3 4 0\n3 -4 0").unwrap();
+        for branching in [
+            BranchingHeuristic::Evsids { decay: 0.8 },
+            BranchingHeuristic::Vmtf,
+            BranchingHeuristic::Chb,
+        ] {
+            let config = SolverConfig {
+                branching,
+                restart: RestartStrategy::Luby { unit: 1 },
+                ..SolverConfig::default()
+            };
+            assert!(
+                !solve_cdcl(&unsat, &config).is_satisfiable(),
+                "Expected UNSAT for branching {branching:?}"
+            );
+            let result = solve_cdcl(&sat, &config);
+            assert!(check_assignment(&sat, &result.into_assignment().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_chronological_backtracking_agrees() {
+        let unsat = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let sat = parse_dimacs_str(b"\np cnf 5 4\n1 2 0\n1 -2 0\n3 4 0\n3 -4 0").unwrap();
+        // Every backtrack is chronological.
+        let config = SolverConfig {
+            chronological_backtracking: true,
+            chronological_threshold: 0,
+            ..SolverConfig::default()
+        };
+        assert!(!solve_cdcl(&unsat, &config).is_satisfiable());
+        let result = solve_cdcl(&sat, &config);
+        assert!(check_assignment(&sat, &result.into_assignment().unwrap()));
+    }
+
+    #[test]
+    fn test_trail_reuse_waits_for_restarts_without_simplification() {
+        let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let config = SolverConfig {
+            restart: RestartStrategy::Luby { unit: 1 },
+            reuse_trail: true,
+            ..SolverConfig::default()
+        };
+        let (result, stats) = solve_cdcl_with_stats(&cnf, &config);
+        assert!(!result.is_satisfiable());
+        assert!(stats.reused_levels > 0);
+
+        // Something simplifies at every restart.
+        let config = SolverConfig {
+            subsumption_interval: 1,
+            probe_interval: 1,
+            vivification_interval: 1,
+            ..config
+        };
+        let (result, stats) = solve_cdcl_with_stats(&cnf, &config);
+        assert!(!result.is_satisfiable());
+        assert_eq!(stats.reused_levels, 0);
+    }
+
+    #[test]
+    fn test_seed_makes_runs_reproducible() {
+        let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
+        for branching in [BranchingHeuristic::default(), BranchingHeuristic::Vmtf] {
+            let proof = |seed| {
+                let config = SolverConfig {
+                    branching,
+                    seed,
+                    random_var_frequency: 0.2,
+                    random_polarity_frequency: 0.5,
+                    initial_shuffle: true,
+                    proof: true,
+                    ..SolverConfig::default()
+                };
+                solve_cdcl(&cnf, &config).unsat_proof().unwrap().to_vec()
+            };
+            assert_eq!(proof(1), proof(1));
+            assert_ne!(proof(1), proof(2));
+        }
+    }
+
     #[test]
     fn test_geometric_restart_terminates_unsat() {
         let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
@@ -319,6 +812,22 @@ mod tests {
                 factor: 1.5,
             },
             deletion: DeletionStrategy::None,
+            ..SolverConfig::default()
+        };
+        assert!(!solve_cdcl(&cnf, &config).is_satisfiable());
+    }
+
+    #[test]
+    fn test_glucose_restart_terminates_unsat() {
+        let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let config = SolverConfig {
+            polarity: PolarityHeuristic::AlwaysFalse,
+            restart: RestartStrategy::Glucose {
+                margin: 1.25,
+                blocking: 1.4,
+            },
+            deletion: DeletionStrategy::None,
+            ..SolverConfig::default()
         };
         assert!(!solve_cdcl(&cnf, &config).is_satisfiable());
     }
@@ -328,14 +837,31 @@ mod tests {
         let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
         let configs = [
             SolverConfig {
+                branching: BranchingHeuristic::default(),
                 polarity: PolarityHeuristic::AlwaysFalse,
                 restart: RestartStrategy::None,
                 deletion: DeletionStrategy::None,
+                minimization: ClauseMinimization::None,
+                preprocess: false,
+                proof: false,
+                subsumption_interval: 1,
+                vivification_budget: 0,
+                vivification_interval: 1,
+                probe_budget: 20_000,
+                probe_interval: 1,
+                seed: 0,
+                random_var_frequency: 0.0,
+                random_polarity_frequency: 0.0,
+                initial_shuffle: false,
+                chronological_backtracking: false,
+                chronological_threshold: 100,
+                reuse_trail: false,
             },
             SolverConfig {
                 polarity: PolarityHeuristic::PhaseSaving,
                 restart: RestartStrategy::Luby { unit: 1 },
                 deletion: DeletionStrategy::Lbd { max_lbd: 3 },
+                ..SolverConfig::default()
             },
             SolverConfig {
                 polarity: PolarityHeuristic::AlwaysTrue,
@@ -344,6 +870,8 @@ mod tests {
                     factor: 2.0,
                 },
                 deletion: DeletionStrategy::Activity { fraction: 0.5 },
+                preprocess: true,
+                ..SolverConfig::default()
             },
         ];
         for config in &configs {
@@ -360,14 +888,31 @@ mod tests {
             .unwrap();
         let configs = [
             SolverConfig {
+                branching: BranchingHeuristic::default(),
                 polarity: PolarityHeuristic::AlwaysFalse,
                 restart: RestartStrategy::None,
                 deletion: DeletionStrategy::None,
+                minimization: ClauseMinimization::None,
+                preprocess: false,
+                proof: false,
+                subsumption_interval: 1,
+                vivification_budget: 0,
+                vivification_interval: 1,
+                probe_budget: 20_000,
+                probe_interval: 1,
+                seed: 0,
+                random_var_frequency: 0.0,
+                random_polarity_frequency: 0.0,
+                initial_shuffle: false,
+                chronological_backtracking: false,
+                chronological_threshold: 100,
+                reuse_trail: false,
             },
             SolverConfig {
                 polarity: PolarityHeuristic::PhaseSaving,
                 restart: RestartStrategy::Luby { unit: 1 },
                 deletion: DeletionStrategy::Lbd { max_lbd: 3 },
+                ..SolverConfig::default()
             },
             SolverConfig {
                 polarity: PolarityHeuristic::AlwaysTrue,
@@ -376,6 +921,8 @@ mod tests {
                     factor: 1.5,
                 },
                 deletion: DeletionStrategy::Activity { fraction: 0.5 },
+                preprocess: true,
+                ..SolverConfig::default()
             },
         ];
         for config in &configs {
@@ -388,6 +935,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_recursive_minimization() {
+        // x1 forces x2; deciding x3 forces x4 and x5, falsifying (-1 -2 -4 -5).
+        // The 1-UIP clause (-1 -2 -3) minimizes to (-1 -3) since x2 follows from x1.
+        let cnf = parse_dimacs_str(b"p cnf 5 4\n-1 2 0\n-3 4 0\n-3 5 0\n-1 -2 -4 -5 0").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.decide(Var { index: 1 }, Val::True);
+        assert_eq!(state.propagate(), None);
+        state.decide(Var { index: 3 }, Val::True);
+        let Some(falsified) = state.propagate() else {
+            panic!("expected a conflict");
+        };
+
+        let mut conflict = ConflictingLits::new(falsified, &state);
+        for (pos, trail_element) in state.trail.iter().enumerate().rev().take(2) {
+            conflict.update(pos, trail_element);
+        }
+        assert_eq!(conflict.get_backjump_level(), 1);
+        assert_eq!(conflict.minimize(), 1);
+        assert_eq!(
+            conflict.get_learned_clause(),
+            vec![lit(-1).into(), lit(-3).into()]
+        );
+        // The reason of x2 comes first, the falsified clause last.
+        let hints = conflict.get_hints();
+        assert_eq!((hints.len(), hints[0], hints[3]), (4, 1, 4));
+    }
+
     #[test]
     fn test_deletion_lbd_preserves_correctness() {
         let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
@@ -395,6 +970,7 @@ mod tests {
             polarity: PolarityHeuristic::AlwaysFalse,
             restart: RestartStrategy::Luby { unit: 1 },
             deletion: DeletionStrategy::Lbd { max_lbd: 1 },
+            ..SolverConfig::default()
         };
         assert!(!solve_cdcl(&cnf, &config).is_satisfiable());
     }
@@ -406,10 +982,168 @@ mod tests {
             polarity: PolarityHeuristic::AlwaysFalse,
             restart: RestartStrategy::Luby { unit: 1 },
             deletion: DeletionStrategy::Activity { fraction: 0.9 },
+            ..SolverConfig::default()
         };
         assert!(!solve_cdcl(&cnf, &config).is_satisfiable());
     }
 
+    #[test]
+    fn test_deletion_tiered_preserves_correctness() {
+        let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let config = SolverConfig {
+            polarity: PolarityHeuristic::AlwaysFalse,
+            restart: RestartStrategy::Luby { unit: 1 },
+            deletion: DeletionStrategy::Tiered {
+                core_lbd: 1,
+                tier2_lbd: 2,
+                fraction: 0.9,
+            },
+            ..SolverConfig::default()
+        };
+        assert!(!solve_cdcl(&cnf, &config).is_satisfiable());
+    }
+
+    fn lit(index: isize) -> Lit {
+        Lit {
+            var: Var {
+                index: index.unsigned_abs(),
+            },
+            value: if index > 0 { Val::True } else { Val::False },
+        }
+    }
+
+    #[test]
+    fn test_assumptions_sat_respects_assumptions() {
+        let cnf = parse_dimacs_str(b"p cnf 4 3\n1 2 0\n-1 3 0\n-2 4 0\n").unwrap();
+        let mut solver = CdclSolver::new(&cnf, &default_config());
+        let assumptions = [lit(-1), lit(-4)];
+        let result = solver.solve_with_assumptions(&assumptions);
+        // -x1 forces x2, which forces x4: the assumptions conflict.
+        let core = result.failed_assumptions().unwrap();
+        assert!(core.contains(&lit(-1)) && core.contains(&lit(-4)));
+
+        let result = solver.solve_with_assumptions(&[lit(1), lit(-4)]);
+        let assignment = result.assignment().unwrap();
+        assert!(check_assignment(&cnf, assignment));
+        assert_eq!(assignment.get(&lit(1)), Some(true));
+        assert_eq!(assignment.get(&lit(-4)), Some(true));
+    }
+
+    #[test]
+    fn test_failed_assumptions_are_minimal_subset() {
+        let cnf = parse_dimacs_str(b"p cnf 4 2\n-1 2 0\n-2 3 0\n").unwrap();
+        let mut solver = CdclSolver::new(&cnf, &default_config());
+        let result = solver.solve_with_assumptions(&[lit(1), lit(4), lit(-3)]);
+        let mut core = result.failed_assumptions().unwrap().to_vec();
+        core.sort();
+        let mut expected = vec![lit(1), lit(-3)];
+        expected.sort();
+        assert_eq!(core, expected);
+
+        // The formula itself is still satisfiable once the assumptions are dropped.
+        assert!(solver.solve().is_satisfiable());
+    }
+
+    #[test]
+    fn test_incremental_keeps_learned_clauses() {
+        // Pigeonhole 4/3 with pigeon 1 allowed to escape through x13.
+        let mut text = PIGEON_4_3.to_vec();
+        text.splice(..b"p cnf 12 22\n1 2 3 0".len(), *b"p cnf 13 22\n1 2 3 13 0");
+        let cnf = parse_dimacs_str(&text).unwrap();
+        let mut solver = CdclSolver::new(&cnf, &default_config());
+
+        let result = solver.solve_with_assumptions(&[lit(-13)]);
+        assert_eq!(result.failed_assumptions(), Some(&[lit(-13)][..]));
+        assert!(solver.state().formula().clauses.len() > cnf.clauses.len());
+        assert!(solver.state().conflict_count > 0);
+
+        let result = solver.solve();
+        assert!(check_assignment(&cnf, result.assignment().unwrap()));
+    }
+
+    #[test]
+    fn test_incremental_add_clauses_between_solves() {
+        let cnf = parse_dimacs_str(b"p cnf 2 1\n1 2 0\n").unwrap();
+        let mut solver = CdclSolver::new(&cnf, &default_config());
+        assert!(solver.solve().is_satisfiable());
+
+        assert!(solver.add_clause(Clause {
+            literals: vec![lit(-1)],
+        }));
+        let result = solver.solve();
+        assert_eq!(result.assignment().unwrap().get(&lit(2)), Some(true));
+
+        let var = solver.new_var();
+        assert_eq!(var.index, 3);
+        assert!(solver.add_clause(Clause {
+            literals: vec![lit(-2), lit(3)],
+        }));
+        assert!(solver
+            .solve_with_assumptions(&[lit(-3)])
+            .failed_assumptions()
+            .is_some());
+        assert!(solver.add_clause(Clause {
+            literals: vec![lit(-3)],
+        }));
+        assert_eq!(solver.solve(), SolverResult::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_incremental_unsat_without_assumptions() {
+        let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let mut solver = CdclSolver::new(&cnf, &default_config());
+        assert_eq!(solver.solve(), SolverResult::Unsatisfiable);
+        // Once refuted, every later query is UNSAT with no assumptions to blame.
+        assert_eq!(
+            solver.solve_with_assumptions(&[lit(1)]),
+            SolverResult::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_level_0_conflict_comes_before_assumptions() {
+        // -1 is false at level 0, but so is the clause 2 3.
+        let cnf = parse_dimacs_str(b"p cnf 3 4\n1 0\n-2 0\n-3 0\n2 3 0").unwrap();
+        let mut solver = CdclSolver::new(&cnf, &default_config());
+        assert_eq!(
+            solver.solve_with_assumptions(&[lit(-1)]),
+            SolverResult::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_proof_contains_only_lemmas_and_ends_with_empty_clause() {
+        let cnf = parse_dimacs_str(PIGEON_4_3).unwrap();
+        let config = SolverConfig {
+            polarity: PolarityHeuristic::AlwaysFalse,
+            restart: RestartStrategy::Luby { unit: 1 },
+            deletion: DeletionStrategy::Lbd { max_lbd: 1 },
+            proof: true,
+            ..SolverConfig::default()
+        };
+        let result = solve_cdcl(&cnf, &config);
+        let proof = result.unsat_proof().unwrap();
+        assert!(matches!(
+            proof.last(),
+            Some(ProofStep::Add { clause, .. }) if clause.literals.is_empty()
+        ));
+        assert!(proof
+            .iter()
+            .any(|step| matches!(step, ProofStep::Delete { .. })));
+        // Every deleted clause must have been added as a lemma first, under the
+        // same ID. Propagation may have reordered its literals since.
+        let sorted = |clause: &Clause| clause.literals.iter().copied().sorted().collect_vec();
+        for (i, step) in proof.iter().enumerate() {
+            if let ProofStep::Delete { id, clause } = step {
+                assert!(proof[..i].iter().any(|earlier| matches!(
+                    earlier,
+                    ProofStep::Add { id: added, clause: lemma, .. }
+                        if added == id && sorted(lemma) == sorted(clause)
+                )));
+            }
+        }
+    }
+
     // Cross-solver agreement: CDCL, DPLL, and backtrack must agree on SAT/UNSAT
     // for a suite of formulas. This catches incorrect learned clauses or
     // backjump bugs that don't affect termination but do affect correctness.
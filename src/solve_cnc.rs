@@ -1,5 +1,6 @@
 use crate::config::SolverConfig;
 use crate::formula::*;
+use crate::preprocess;
 use crate::solve_cdcl::*;
 use crate::solver_state::*;
 use std::sync::mpsc;
@@ -21,12 +22,10 @@ pub fn solve_cnc(cnf: &CnfFormula, depth: usize, config: &SolverConfig) -> Solve
         }
         match state.get_status() {
             Status::Satisfied => {
-                let _ = tx.send(SolverResult::Satisfiable(
-                    state.assignment.fill_unassigned(),
-                ));
+                let _ = tx.send(SolverResult::Satisfiable(state.model()));
                 vec![]
             }
-            Status::Falsified(_) => {
+            Status::Falsified(..) => {
                 let _ = tx.send(SolverResult::Unsatisfiable);
                 vec![]
             }
@@ -50,15 +49,14 @@ pub fn solve_cnc(cnf: &CnfFormula, depth: usize, config: &SolverConfig) -> Solve
                 }
                 handles
             }
-            Status::UnassignedUnit(lit, clause) => {
-                state.assign_unitprop(lit.var, lit.value, clause);
-                solve_cnc_rec(state, depth, config, tx)
-            }
         }
     }
 
     // Initialize solver state
     let mut blank_state = SolverState::from_cnf(cnf);
+    if config.preprocess {
+        preprocess::preprocess(&mut blank_state);
+    }
     blank_state.pure_literal_eliminate();
     blank_state.seal_original_clauses();
 
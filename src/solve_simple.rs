@@ -13,10 +13,10 @@ pub fn solve_basic(cnf: &CnfFormula) -> SolverResult {
 
 pub fn solve_backtrack(cnf: &CnfFormula) -> SolverResult {
     // Recursively assign each variable to true or false
-    pub fn solve_backtrack_rec(state: SolverState) -> SolverResult {
+    pub fn solve_backtrack_rec(mut state: SolverState) -> SolverResult {
         match state.get_status() {
-            Status::Satisfied => SolverResult::Satisfiable(state.assignment.fill_unassigned()),
-            Status::Falsified(_) => SolverResult::Unsatisfiable,
+            Status::Satisfied => SolverResult::Satisfiable(state.model()),
+            Status::Falsified(..) => SolverResult::Unsatisfiable,
             Status::UnassignedDecision(var) => {
                 let (tstate, fstate) = branch_on_variable(state, var);
                 match solve_backtrack_rec(fstate) {
@@ -24,13 +24,6 @@ pub fn solve_backtrack(cnf: &CnfFormula) -> SolverResult {
                     _ => solve_backtrack_rec(tstate),
                 }
             }
-            Status::UnassignedUnit(lit, clause) => {
-                // The literal is forced; assigning the known direction avoids
-                // exploring a subtree that is guaranteed to be UNSAT.
-                let mut state = state;
-                state.assign_unitprop(lit.var, lit.value, clause);
-                solve_backtrack_rec(state)
-            }
         }
     }
     let blank_state = SolverState::from_cnf(cnf);
@@ -41,8 +34,8 @@ pub fn solve_dpll(cnf: &CnfFormula) -> SolverResult {
     // Recursively assign each variable to true or false
     pub fn solve_dpll_rec(mut state: SolverState) -> SolverResult {
         match state.get_status() {
-            Status::Satisfied => SolverResult::Satisfiable(state.assignment.fill_unassigned()),
-            Status::Falsified(_) => SolverResult::Unsatisfiable,
+            Status::Satisfied => SolverResult::Satisfiable(state.model()),
+            Status::Falsified(..) => SolverResult::Unsatisfiable,
             Status::UnassignedDecision(var) => {
                 let (tstate, fstate) = branch_on_variable(state, var);
                 match solve_dpll_rec(fstate) {
@@ -50,10 +43,6 @@ pub fn solve_dpll(cnf: &CnfFormula) -> SolverResult {
                     _ => solve_dpll_rec(tstate),
                 }
             }
-            Status::UnassignedUnit(lit, clause) => {
-                state.assign_unitprop(lit.var, lit.value, clause);
-                solve_dpll_rec(state)
-            }
         }
     }
     let mut blank_state = SolverState::from_cnf(cnf);
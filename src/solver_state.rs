@@ -1,25 +1,55 @@
-use itertools::Itertools;
+use std::cmp::Reverse;
+use std::collections::HashSet;
 
-use crate::config::DeletionStrategy;
+use itertools::{Either, Itertools};
+use rand::prelude::*;
+
+use crate::branching::Branching;
+pub use crate::clause_db::{ClauseDb, ClauseRef};
+use crate::config::{BranchingHeuristic, DeletionStrategy, SolverConfig};
 use crate::formula::*;
+use crate::preprocess::ReconstructionStack;
 use crate::watch_list::*;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Record {
-    value: Val,
-    decision_level: u32,
-}
-
+// Truth values are stored per literal, so that looking one up is a single
+// index with no sign arithmetic; levels are stored per variable.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assignment {
-    assignment: Vec<Option<Record>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SolverResult {
     Satisfiable(Assignment),
     Unsatisfiable,
-    UnsatisfiableWithProof(Vec<Clause>),
+    UnsatisfiableWithProof(Vec<ProofStep>),
+    UnsatisfiableUnderAssumptions(Vec<Lit>),
+}
+
+/// Identifies a clause for the lifetime of a solver. Original clauses are
+/// numbered 1..=m in input order, as LRAT expects; later clauses count up from there.
+pub type ClauseId = u64;
+
+/// One line of a clausal proof: a lemma addition or a clause deletion.
+///
+/// `hints` lists the antecedents of a lemma in unit propagation order, which
+/// is what LRAT needs; DRAT output simply ignores them along with the IDs.
+/// A lemma that is only RAT on its first literal also lists, in `rat_hints`,
+/// every clause containing the negation of that literal, with the hints
+/// deriving their resolvent with the lemma.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofStep {
+    Add {
+        id: ClauseId,
+        clause: Clause,
+        hints: Vec<ClauseId>,
+        rat_hints: Vec<(ClauseId, Vec<ClauseId>)>,
+    },
+    Delete {
+        id: ClauseId,
+        clause: Clause,
+    },
 }
 
 impl SolverResult {
@@ -40,9 +70,16 @@ impl SolverResult {
         }
     }
 
-    pub fn unsat_proof(&self) -> Option<Vec<Clause>> {
+    pub fn unsat_proof(&self) -> Option<&[ProofStep]> {
         match self {
-            Self::UnsatisfiableWithProof(proof) => Some(proof.clone()),
+            Self::UnsatisfiableWithProof(proof) => Some(proof),
+            _ => None,
+        }
+    }
+
+    pub fn failed_assumptions(&self) -> Option<&[Lit]> {
+        match self {
+            Self::UnsatisfiableUnderAssumptions(core) => Some(core),
             _ => None,
         }
     }
@@ -51,62 +88,73 @@ impl SolverResult {
 impl Assignment {
     pub fn empty(num_vars: usize) -> Self {
         Self {
-            assignment: vec![None; num_vars],
+            values: vec![None; num_vars * 2],
+            levels: vec![0; num_vars],
         }
     }
     pub fn get(&self, lit: &Lit) -> Option<bool> {
-        self.assignment[lit.var.index - 1].map(|v| v.value == lit.value)
+        self.value(PackedLit::from(*lit))
     }
     pub fn get_decision_level(&self, lit: &Lit) -> Option<u32> {
-        self.assignment[lit.var.index - 1].map(|v| v.decision_level)
+        self.level(PackedLit::from(*lit))
     }
     pub fn set(&mut self, var: Var, value: Val, decision_level: u32) {
-        self.assignment[var.index - 1] = Some(Record {
-            value,
-            decision_level,
-        });
+        self.assign(PackedLit::new(var.index - 1, value), decision_level);
+    }
+    pub fn value(&self, lit: PackedLit) -> Option<bool> {
+        self.values[lit.index()]
+    }
+    pub fn level(&self, lit: PackedLit) -> Option<u32> {
+        self.value(lit).map(|_| self.levels[lit.var_index()])
+    }
+    // Make `lit` true.
+    pub fn assign(&mut self, lit: PackedLit, decision_level: u32) {
+        self.values[lit.index()] = Some(true);
+        self.values[lit.not().index()] = Some(false);
+        self.levels[lit.var_index()] = decision_level;
+    }
+    pub fn unassign(&mut self, lit: PackedLit) {
+        self.values[lit.index()] = None;
+        self.values[lit.not().index()] = None;
     }
     pub fn get_unassigned_var(&self) -> Option<Var> {
-        self.assignment
+        self.values
             .iter()
+            .step_by(2)
             .position(|v| v.is_none())
             .map(|n| Var { index: n + 1 })
     }
-    pub fn fill_unassigned(self) -> Self {
-        Self {
-            assignment: self
-                .assignment
-                .iter()
-                .map(|v| {
-                    v.or(Some(Record {
-                        value: Val::False,
-                        decision_level: u32::MAX,
-                    }))
-                })
-                .collect::<Vec<_>>(),
+    pub fn fill_unassigned(mut self) -> Self {
+        for var_index in 0..self.num_vars() {
+            let lit = PackedLit::new(var_index, Val::False);
+            if self.value(lit).is_none() {
+                self.assign(lit, u32::MAX);
+            }
         }
+        self
     }
     pub fn num_vars(&self) -> usize {
-        self.assignment.len()
+        self.levels.len()
+    }
+    pub fn add_var(&mut self) {
+        self.values.extend([None, None]);
+        self.levels.push(0);
+    }
+    // Forget every variable past the first `num_vars`.
+    pub fn truncate(&mut self, num_vars: usize) {
+        self.values.truncate(num_vars * 2);
+        self.levels.truncate(num_vars);
     }
     pub fn every_possible(num_vars: usize) -> impl Iterator<Item = Self> {
-        std::iter::repeat_n(
-            [
-                Some(Record {
-                    value: Val::False,
-                    decision_level: 0,
-                }),
-                Some(Record {
-                    value: Val::True,
-                    decision_level: 0,
-                }),
-            ],
-            num_vars,
-        )
-        .multi_cartesian_product()
-        .map(|v| Self {
-            assignment: v.to_vec(),
-        })
+        std::iter::repeat_n([Val::False, Val::True], num_vars)
+            .multi_cartesian_product()
+            .map(move |values| {
+                let mut assignment = Self::empty(num_vars);
+                for (var_index, value) in values.into_iter().enumerate() {
+                    assignment.assign(PackedLit::new(var_index, value), 0);
+                }
+                assignment
+            })
     }
 }
 
@@ -115,11 +163,14 @@ impl std::fmt::Display for Assignment {
         write!(
             f,
             "[{}]",
-            self.assignment
-                .iter()
-                .enumerate()
-                .filter_map(|(i, v)| {
-                    v.map(|v| format!("x{}={}(d{})", i + 1, v.value, v.decision_level))
+            (0..self.num_vars())
+                .filter_map(|var_index| {
+                    let value = match self.value(PackedLit::new(var_index, Val::True))? {
+                        true => Val::True,
+                        false => Val::False,
+                    };
+                    let level = self.levels[var_index];
+                    Some(format!("x{}={}(d{})", var_index + 1, value, level))
                 })
                 .join(", ")
         )
@@ -143,118 +194,74 @@ impl Clause {
     }
 }
 
-// Indexed max-heap for VSIDS decision ordering. Supports O(log n) insert,
-// remove, and priority-increase (sift_up only — activity is monotone increasing).
 #[derive(Clone, Debug, PartialEq)]
-struct ActivityHeap {
-    heap: Vec<usize>,        // heap positions → 0-based var indices
-    pos: Vec<Option<usize>>, // 0-based var index → heap position (None if absent)
-}
-
-impl ActivityHeap {
-    fn new(n: usize) -> Self {
-        Self {
-            heap: (0..n).collect(),
-            pos: (0..n).map(Some).collect(),
-        }
-    }
-
-    fn peek(&self) -> Option<usize> {
-        self.heap.first().copied()
-    }
-
-    fn sift_up(&mut self, mut p: usize, activity: &[f64]) {
-        while p > 0 {
-            let parent = (p - 1) / 2;
-            if activity[self.heap[p]] > activity[self.heap[parent]] {
-                self.pos[self.heap[p]] = Some(parent);
-                self.pos[self.heap[parent]] = Some(p);
-                self.heap.swap(p, parent);
-                p = parent;
-            } else {
-                break;
-            }
-        }
-    }
-
-    fn sift_down(&mut self, mut p: usize, activity: &[f64]) {
-        let n = self.heap.len();
-        loop {
-            let left = 2 * p + 1;
-            let right = 2 * p + 2;
-            let mut largest = p;
-            if left < n && activity[self.heap[left]] > activity[self.heap[largest]] {
-                largest = left;
-            }
-            if right < n && activity[self.heap[right]] > activity[self.heap[largest]] {
-                largest = right;
-            }
-            if largest == p {
-                break;
-            }
-            self.pos[self.heap[p]] = Some(largest);
-            self.pos[self.heap[largest]] = Some(p);
-            self.heap.swap(p, largest);
-            p = largest;
-        }
-    }
-
-    fn insert(&mut self, var_idx: usize, activity: &[f64]) {
-        if self.pos[var_idx].is_none() {
-            let p = self.heap.len();
-            self.heap.push(var_idx);
-            self.pos[var_idx] = Some(p);
-            self.sift_up(p, activity);
-        }
-    }
-
-    fn remove(&mut self, var_idx: usize, activity: &[f64]) {
-        let Some(p) = self.pos[var_idx].take() else {
-            return;
-        };
-        let last = self.heap.len() - 1;
-        if p != last {
-            let moved = self.heap[last];
-            self.heap[p] = moved;
-            self.pos[moved] = Some(p);
-            self.heap.pop();
-            self.sift_up(p, activity);
-            if self.pos[moved] == Some(p) {
-                self.sift_down(p, activity);
-            }
-        } else {
-            self.heap.pop();
-        }
-    }
-
-    fn update(&mut self, var_idx: usize, activity: &[f64]) {
-        if let Some(p) = self.pos[var_idx] {
-            self.sift_up(p, activity);
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq)]
-struct LearnedClauseMeta {
+struct LearnedClause {
+    cref: ClauseRef,
     lbd: u32,
     activity: f64,
+    // Whether conflict analysis has used the clause since the last deletion.
+    used: bool,
+}
+
+/// Counters accumulated over the lifetime of a solver.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverStats {
+    pub minimized_literals: u64,
+    pub subsumed_clauses: u64,
+    pub strengthened_clauses: u64,
+    pub probed_units: u64,
+    pub hyper_binary_resolvents: u64,
+    pub substituted_vars: u64,
+    pub eliminated_clauses: u64,
+    pub vivified_literals: u64,
+    pub added_vars: u64,
+    pub chronological_backtracks: u64,
+    pub reused_levels: u64,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct SolverState {
-    pub formula: CnfFormula,
+    pub num_vars: usize,
+    // Variables the caller knows about, as opposed to those introduced by
+    // preprocessing, which always come after them.
+    original_num_vars: usize,
+    pub clauses: ClauseDb,
     pub assignment: Assignment,
     pub trail: Vec<TrailElement>,
     pub decision_level: u32,
+    // Trail length at the start of each decision level above 0.
+    trail_lim: Vec<usize>,
+    // Trail elements before this index have been propagated.
+    qhead: usize,
     watch_list: WatchList,
-    activity: Vec<f64>,
-    var_inc: f64,
-    var_heap: ActivityHeap,
+    // Whether backtracking may keep literals above the level it goes back
+    // to, so that the trail is no longer ordered by level.
+    chronological: bool,
+    // Whether restarts keep the levels that deciding again would rebuild.
+    reuse_trail: bool,
+    branching: Branching,
+    // Source of random decisions.
+    rng: StdRng,
     phase: Vec<Val>,
+    // Phases along the longest conflict-free trail since the last restart or
+    // rephasing, and since the best phases were last taken, with its length.
+    target_phase: Vec<Val>,
+    target_len: usize,
+    best_phase: Vec<Val>,
+    best_len: usize,
     pub conflict_count: u32,
-    learned_from: usize,
-    clause_meta: Vec<LearnedClauseMeta>,
+    pub stats: SolverStats,
+    // Clauses that are never deleted: the input, plus anything added or sealed since.
+    original: Vec<ClauseRef>,
+    // Sorted by `cref`, as handles are allocated in increasing order and keep
+    // it through garbage collection.
+    learned: Vec<LearnedClause>,
     clause_act_inc: f64,
+    next_clause_id: ClauseId,
+    // Log of learned clauses and deletions, if enabled.
+    proof: Option<Vec<ProofStep>>,
+    // Clauses removed by preprocessing, needed to complete a model.
+    pub reconstruction: ReconstructionStack,
 }
 
 impl std::fmt::Display for SolverState {
@@ -262,107 +269,389 @@ impl std::fmt::Display for SolverState {
         write!(
             f,
             "Assignment: {}\nFormula: {}",
-            self.assignment, self.formula
+            self.assignment,
+            self.formula()
         )
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum TrailReason {
-    // At a decision, we snapshot the previous assignment so we can backjump to it if needed.
-    Decision(Assignment),
+    Decision,
     // At unit propagation we save the clause that was used to infer the unit literal.
-    UnitProp(Clause),
+    UnitProp(ClauseRef),
+    // Implied by a binary clause, whose other literal is kept alongside so
+    // that analysis need not read the clause.
+    Binary { other: PackedLit, cref: ClauseRef },
+}
+
+impl TrailReason {
+    /// The clause that implied the literal, unless it was a decision.
+    pub fn cref(&self) -> Option<ClauseRef> {
+        match *self {
+            TrailReason::Decision => None,
+            TrailReason::UnitProp(cref) | TrailReason::Binary { cref, .. } => Some(cref),
+        }
+    }
 }
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct TrailElement {
-    pub lit: Lit,
+    pub lit: PackedLit,
     pub reason: TrailReason,
 }
 
 impl std::fmt::Display for TrailElement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.reason {
-            TrailReason::Decision(_) => write!(f, "{}(D)", self.lit),
-            TrailReason::UnitProp(_) => write!(f, "{}(U)", self.lit),
+            TrailReason::Decision => write!(f, "{}(D)", self.lit),
+            TrailReason::UnitProp(..) | TrailReason::Binary { .. } => {
+                write!(f, "{}(U)", self.lit)
+            }
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Status {
     Satisfied,
-    Falsified(Clause),
+    Falsified(ClauseRef),
     UnassignedDecision(Var),
-    UnassignedUnit(Lit, Clause),
 }
 
 impl SolverState {
     pub fn from_cnf(cnf: &CnfFormula) -> Self {
+        let mut clauses = ClauseDb::new();
+        let original = cnf
+            .clauses
+            .iter()
+            .zip(1..)
+            .map(|(clause, id)| {
+                let literals = clause.literals.iter().map(|&lit| lit.into()).collect_vec();
+                clauses.alloc(id, &literals)
+            })
+            .collect();
         let mut state = Self {
-            formula: cnf.clone(),
+            num_vars: cnf.num_vars,
+            original_num_vars: cnf.num_vars,
+            clauses,
             assignment: Assignment::empty(cnf.num_vars),
             trail: vec![],
             decision_level: 0,
+            trail_lim: vec![],
+            qhead: 0,
             watch_list: WatchList::new(cnf.num_vars),
-            activity: vec![0.0; cnf.num_vars],
-            var_inc: 1.0,
-            var_heap: ActivityHeap::new(cnf.num_vars),
+            chronological: false,
+            reuse_trail: false,
+            branching: Branching::new(BranchingHeuristic::default(), cnf.num_vars),
+            rng: StdRng::seed_from_u64(0),
             phase: vec![Val::False; cnf.num_vars],
+            target_phase: vec![Val::False; cnf.num_vars],
+            target_len: 0,
+            best_phase: vec![Val::False; cnf.num_vars],
+            best_len: 0,
             conflict_count: 0,
-            learned_from: cnf.clauses.len(),
-            clause_meta: vec![],
+            stats: SolverStats::default(),
+            original,
+            learned: vec![],
             clause_act_inc: 1.0,
+            next_clause_id: cnf.clauses.len() as ClauseId + 1,
+            proof: None,
+            reconstruction: ReconstructionStack::default(),
         };
-        state.initialize_watches();
+        state.rebuild_watches();
         state
     }
 
-    fn initialize_watches(&mut self) {
-        for (clause_idx, clause) in self.formula.clauses.iter().enumerate() {
-            self.watch_list.add_clause(clause_idx, clause);
+    // Original clauses first, then learned ones, in the order they were added.
+    fn clause_refs(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        self.original
+            .iter()
+            .copied()
+            .chain(self.learned.iter().map(|learned| learned.cref))
+    }
+
+    /// Every clause currently in the database, as a standalone formula.
+    pub fn formula(&self) -> CnfFormula {
+        CnfFormula {
+            num_vars: self.num_vars,
+            clauses: self
+                .clause_refs()
+                .map(|cref| self.clauses.clause(cref))
+                .collect(),
         }
     }
 
-    pub fn next_decision_var(&self) -> Option<Var> {
-        self.var_heap.peek().map(|i| Var { index: i + 1 })
+    /// The literals directly implied by `lit` through binary clauses. Together
+    /// these form the binary implication graph.
+    pub fn binary_implications(&self, lit: PackedLit) -> &[Implication] {
+        self.watch_list.implications(lit)
+    }
+
+    /// The other literals of the clause that implied `trail_element`, all of
+    /// them false. Binary reasons are answered without reading the clause.
+    pub fn reason_literals(
+        &self,
+        trail_element: &TrailElement,
+    ) -> impl Iterator<Item = PackedLit> + '_ {
+        let lit = trail_element.lit;
+        match trail_element.reason {
+            TrailReason::Decision => Either::Left(None.into_iter()),
+            TrailReason::Binary { other, .. } => Either::Left(Some(other).into_iter()),
+            TrailReason::UnitProp(cref) => Either::Right(
+                self.clauses
+                    .literals(cref)
+                    .iter()
+                    .copied()
+                    .filter(move |&other| other != lit),
+            ),
+        }
+    }
+
+    /// IDs of the reasons the assignment of `literals` transitively depends
+    /// on, in trail order. Given the decisions involved, unit propagation
+    /// through them assigns every literal again.
+    pub fn implication_hints(&self, literals: &[PackedLit]) -> Vec<ClauseId> {
+        let mut seen = vec![false; self.num_vars];
+        for lit in literals {
+            seen[lit.var_index()] = true;
+        }
+
+        let mut hints = Vec::new();
+        for trail_element in self.trail.iter().rev() {
+            if !seen[trail_element.lit.var_index()] {
+                continue;
+            }
+            if let Some(cref) = trail_element.reason.cref() {
+                hints.push(self.clauses.id(cref));
+                for lit in self.reason_literals(trail_element) {
+                    seen[lit.var_index()] = true;
+                }
+            }
+        }
+        hints.reverse();
+        hints
+    }
+
+    /// The clauses that are never deleted, including any added or sealed
+    /// since construction.
+    pub fn original_clauses(&self) -> &[ClauseRef] {
+        &self.original
+    }
+
+    /// A complete assignment satisfying the formula as given, including any
+    /// clauses removed by preprocessing. Only meaningful once the current
+    /// assignment satisfies every remaining clause.
+    pub fn model(&self) -> Assignment {
+        let mut model = self.assignment.clone().fill_unassigned();
+        self.reconstruction.extend(&mut model);
+        model.truncate(self.original_num_vars);
+        model
+    }
+
+    /// The ID the next clause will get. Every clause added so far has a smaller one.
+    pub fn next_clause_id(&self) -> ClauseId {
+        self.next_clause_id
+    }
+
+    pub fn learned_clauses(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        self.learned.iter().map(|learned| learned.cref)
+    }
+
+    pub fn num_learned_clauses(&self) -> usize {
+        self.learned.len()
+    }
+
+    pub fn next_decision_var(&mut self) -> Option<Var> {
+        self.branching.next(&self.assignment)
+    }
+
+    /// Search as `config` asks, starting over from no conflicts and
+    /// reseeding random decisions.
+    pub fn configure_search(&mut self, config: &SolverConfig) {
+        self.chronological = config.chronological_backtracking;
+        self.reuse_trail = config.reuse_trail;
+        self.rng = StdRng::seed_from_u64(config.seed);
+        self.branching = Branching::new(config.branching, self.num_vars);
+        if config.initial_shuffle {
+            self.branching.shuffle(&mut self.rng);
+        }
+        for element in &self.trail {
+            self.branching.assigned(element.lit.var_index());
+        }
+    }
+
+    /// With probability `frequency`, a random unassigned variable to decide on
+    /// instead of the heuristic's choice.
+    pub fn random_decision_var(&mut self, frequency: f64) -> Option<Var> {
+        if frequency == 0.0 || !self.rng.random_bool(frequency) {
+            return None;
+        }
+        let var_index = self.rng.random_range(0..self.num_vars);
+        let lit = PackedLit::new(var_index, Val::True);
+        self.assignment.value(lit).is_none().then_some(Var {
+            index: var_index + 1,
+        })
+    }
+
+    /// With probability `frequency`, a random polarity to decide on instead
+    /// of the heuristic's choice.
+    pub fn random_polarity(&mut self, frequency: f64) -> Option<Val> {
+        if frequency == 0.0 || !self.rng.random_bool(frequency) {
+            return None;
+        }
+        Some(if self.rng.random_bool(0.5) {
+            Val::True
+        } else {
+            Val::False
+        })
     }
 
     pub fn get_phase(&self, var: Var) -> Val {
         self.phase[var.index - 1]
     }
 
-    pub fn bump_var_activity(&mut self, clause: &Clause) {
-        for lit in &clause.literals {
-            let i = lit.var.index - 1;
-            self.activity[i] += self.var_inc;
-            let heap = &mut self.var_heap;
-            let activity = &self.activity;
-            heap.update(i, activity);
+    pub fn set_phase(&mut self, var: Var, value: Val) {
+        self.phase[var.index - 1] = value;
+    }
+
+    pub fn get_target_phase(&self, var: Var) -> Val {
+        self.target_phase[var.index - 1]
+    }
+
+    /// Remember the phases of every level below the current one, which
+    /// propagated without a conflict, if that is a longer trail than the
+    /// target or best one. Call on every conflict.
+    pub fn update_target_phases(&mut self) {
+        let len = self.trail_lim.last().copied().unwrap_or(0);
+        if len > self.target_len {
+            for element in &self.trail[..len] {
+                self.target_phase[element.lit.var_index()] = element.lit.value();
+            }
+            self.target_len = len;
         }
-        self.var_inc *= 1.05;
-        if self.var_inc > 1e100 {
-            for a in &mut self.activity {
-                *a /= 1e100;
+        if len > self.best_len {
+            for element in &self.trail[..len] {
+                self.best_phase[element.lit.var_index()] = element.lit.value();
             }
-            self.var_inc /= 1e100;
-            // Relative order is preserved by uniform rescaling, so the heap
-            // remains valid — no rebuild needed.
+            self.best_len = len;
+        }
+    }
+
+    /// The best phases so far, which start over from here.
+    pub fn take_best_phases(&mut self) -> Vec<Val> {
+        self.best_len = 0;
+        self.best_phase.clone()
+    }
+
+    /// Replace both the saved and the target phases of every variable.
+    pub fn rephase(&mut self, phases: Vec<Val>) {
+        debug_assert_eq!(phases.len(), self.num_vars);
+        self.target_phase = phases.clone();
+        self.target_len = 0;
+        self.phase = phases;
+    }
+
+    pub fn bump_var_activity(&mut self, literals: &[PackedLit]) {
+        self.branching
+            .bump(literals, &self.assignment, self.conflict_count);
+    }
+
+    /// Start logging every learned and deleted clause as a proof.
+    pub fn enable_proof(&mut self) {
+        self.proof.get_or_insert_with(Vec::new);
+    }
+
+    /// Log the empty clause, derived from the given antecedents. Only
+    /// meaningful once a conflict has been found at decision level 0.
+    pub fn conclude_proof(&mut self, hints: Vec<ClauseId>) {
+        let id = self.next_clause_id;
+        if let Some(proof) = &mut self.proof {
+            proof.push(ProofStep::Add {
+                id,
+                clause: Clause { literals: vec![] },
+                hints,
+                rat_hints: vec![],
+            });
         }
     }
 
+    pub fn take_proof(&mut self) -> Option<Vec<ProofStep>> {
+        self.proof.take()
+    }
+
     pub fn seal_original_clauses(&mut self) {
-        self.learned_from = self.formula.clauses.len();
+        self.original
+            .extend(self.learned.drain(..).map(|learned| learned.cref));
+    }
+
+    /// Add a fresh variable to the formula and return it.
+    ///
+    /// Any assignment above decision level 0 is discarded first.
+    pub fn new_var(&mut self) -> Var {
+        debug_assert_eq!(self.num_vars, self.original_num_vars);
+        self.original_num_vars += 1;
+        self.new_auxiliary_var()
     }
 
-    fn compute_lbd(&self, clause: &Clause) -> u32 {
-        let mut levels: Vec<u32> = clause
+    /// Add a fresh variable that is left out of the model, for preprocessing
+    /// to define in terms of the others.
+    pub fn new_auxiliary_var(&mut self) -> Var {
+        self.backjump_to_decision_level(0);
+        self.num_vars += 1;
+        self.assignment.add_var();
+        self.watch_list.add_var();
+        self.phase.push(Val::False);
+        self.target_phase.push(Val::False);
+        self.best_phase.push(Val::False);
+        self.branching.add_var();
+        Var {
+            index: self.num_vars,
+        }
+    }
+
+    /// Add a permanent clause to the formula. Unlike learned clauses, it is
+    /// never considered for deletion.
+    ///
+    /// Any assignment above decision level 0 is discarded first. Returns false
+    /// if the clause is empty, in which case the formula is now UNSAT.
+    pub fn add_clause(&mut self, clause: Clause) -> bool {
+        self.backjump_to_decision_level(0);
+
+        let mut literals = clause
             .literals
+            .into_iter()
+            .map(PackedLit::from)
+            .collect_vec();
+        literals.sort_unstable();
+        literals.dedup();
+        // After sorting, x and -x are adjacent.
+        if literals
+            .windows(2)
+            .any(|w| w[0].var_index() == w[1].var_index())
+        {
+            return true;
+        }
+        if literals.is_empty() {
+            return false;
+        }
+
+        let cref = self.clauses.alloc(self.next_clause_id, &literals);
+        self.next_clause_id += 1;
+        self.original.push(cref);
+        // Replaying the trail lets the watches see literals already false at level 0.
+        self.rebuild_watches();
+        true
+    }
+
+    /// The number of distinct decision levels among `literals`, all of which
+    /// must be assigned.
+    pub fn compute_lbd(&self, literals: &[PackedLit]) -> u32 {
+        let mut levels: Vec<u32> = literals
             .iter()
-            .map(|lit| {
+            .map(|&lit| {
                 self.assignment
-                    .get_decision_level(lit)
+                    .level(lit)
                     .expect("compute_lbd called with unassigned literal in learned clause")
             })
             .collect();
@@ -371,38 +660,66 @@ impl SolverState {
         levels.len() as u32
     }
 
-    pub fn learn_clause_with_meta(&mut self, clause: Clause) {
-        let lbd = self.compute_lbd(&clause);
-        self.clause_meta.push(LearnedClauseMeta {
+    /// Learn a clause found by conflict analysis. Its literals are stored
+    /// with the one at the highest decision level first.
+    pub fn learn_clause_with_meta(
+        &mut self,
+        literals: Vec<PackedLit>,
+        hints: Vec<ClauseId>,
+    ) -> ClauseRef {
+        let lbd = self.compute_lbd(&literals);
+        let cref = self.learn_clause(literals, hints);
+        self.learned.push(LearnedClause {
+            cref,
             lbd,
             activity: self.clause_act_inc,
+            used: true,
         });
         self.clause_act_inc *= 1.001;
         if self.clause_act_inc > 1e100 {
-            for m in &mut self.clause_meta {
-                m.activity /= 1e100;
+            for learned in &mut self.learned {
+                learned.activity /= 1e100;
             }
             self.clause_act_inc /= 1e100;
         }
-        self.learn_clause(clause);
+        cref
+    }
+
+    /// Bump the activity of every learned clause among `crefs`, which took
+    /// part in the current conflict, and lower its LBD if it now spans fewer
+    /// decision levels. Every literal of them must be assigned.
+    pub fn bump_clause_activity(&mut self, crefs: &[ClauseRef]) {
+        for &cref in crefs {
+            let Ok(pos) = self
+                .learned
+                .binary_search_by_key(&cref, |learned| learned.cref)
+            else {
+                continue;
+            };
+            let lbd = self.compute_lbd(self.clauses.literals(cref));
+            let learned = &mut self.learned[pos];
+            learned.activity += self.clause_act_inc;
+            learned.lbd = learned.lbd.min(lbd);
+            learned.used = true;
+        }
     }
 
     pub fn delete_weak_learned_clauses(&mut self, strategy: &DeletionStrategy) {
-        let n_learned = self.formula.clauses.len() - self.learned_from;
+        let n_learned = self.learned.len();
         if n_learned == 0 {
             return;
         }
 
-        let keep: Vec<bool> = match strategy {
+        let mut keep: Vec<bool> = match strategy {
             DeletionStrategy::None => return,
             DeletionStrategy::Lbd { max_lbd } => {
-                self.clause_meta.iter().map(|m| m.lbd <= *max_lbd).collect()
+                self.learned.iter().map(|l| l.lbd <= *max_lbd).collect()
             }
             DeletionStrategy::Activity { fraction } => {
                 let mut indexed: Vec<(usize, f64)> = self
-                    .clause_meta
+                    .learned
                     .iter()
-                    .map(|m| m.activity)
+                    .map(|l| l.activity)
                     .enumerate()
                     .collect();
                 indexed.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
@@ -413,47 +730,270 @@ impl SolverState {
                 }
                 keep
             }
+            DeletionStrategy::Tiered {
+                core_lbd,
+                tier2_lbd,
+                fraction,
+            } => {
+                // Core clauses stay for good, and tier-2 ones for as long as
+                // they keep being used. The rest compete on activity.
+                let mut keep = self
+                    .learned
+                    .iter()
+                    .map(|l| l.lbd <= *core_lbd || (l.lbd <= *tier2_lbd && l.used))
+                    .collect_vec();
+                let local = (0..n_learned)
+                    .filter(|&i| !keep[i])
+                    .sorted_by(|&i, &j| {
+                        let activity = |k: usize| self.learned[k].activity;
+                        activity(j).partial_cmp(&activity(i)).unwrap()
+                    })
+                    .collect_vec();
+                let keep_count = ((local.len() as f64) * (1.0 - fraction)).ceil() as usize;
+                for i in local.into_iter().take(keep_count) {
+                    keep[i] = true;
+                }
+                keep
+            }
         };
 
-        let mut new_learned: Vec<Clause> = Vec::new();
-        let mut new_meta: Vec<LearnedClauseMeta> = Vec::new();
+        // Reasons for literals still on the trail are kept, so that their
+        // references stay valid and their IDs remain antecedents for the proof.
+        let locked: HashSet<ClauseRef> =
+            self.trail.iter().filter_map(|e| e.reason.cref()).collect();
+        for (learned, should_keep) in self.learned.iter().zip(&mut keep) {
+            *should_keep |= locked.contains(&learned.cref);
+        }
 
-        for (offset, should_keep) in keep.into_iter().enumerate() {
+        let learned = std::mem::take(&mut self.learned);
+        for (mut learned, should_keep) in learned.into_iter().zip(keep) {
             if should_keep {
-                new_learned.push(self.formula.clauses[self.learned_from + offset].clone());
-                new_meta.push(self.clause_meta[offset].clone());
+                learned.used = false;
+                self.learned.push(learned);
+                continue;
+            }
+            if let Some(proof) = &mut self.proof {
+                proof.push(ProofStep::Delete {
+                    id: self.clauses.id(learned.cref),
+                    clause: self.clauses.clause(learned.cref),
+                });
             }
+            self.clauses.free(learned.cref);
         }
 
-        self.formula.clauses.truncate(self.learned_from);
-        self.formula.clauses.extend(new_learned);
-        self.clause_meta = new_meta;
+        if self.clauses.wasted() > 0 {
+            self.collect_garbage();
+        }
+        self.rebuild_watches();
+    }
+
+    /// Add a clause that follows from the formula as a permanent one, logging
+    /// it with the antecedents in `hints`. Like `remove_clause`, it is only
+    /// for simplification at decision level 0, and leaves the watches stale
+    /// until `finish_simplification`.
+    pub fn derive_clause(&mut self, literals: Vec<PackedLit>, hints: Vec<ClauseId>) -> ClauseRef {
+        let cref = self.alloc_derived(literals, hints, vec![]);
+        self.original.push(cref);
+        cref
+    }
+
+    /// Like `derive_clause`, but for a redundant clause, which joins the
+    /// learned clauses with the given LBD and may be deleted like them.
+    pub fn derive_learned_clause(
+        &mut self,
+        literals: Vec<PackedLit>,
+        hints: Vec<ClauseId>,
+        lbd: u32,
+    ) -> ClauseRef {
+        let cref = self.alloc_derived(literals, hints, vec![]);
+        self.learned.push(LearnedClause {
+            cref,
+            lbd,
+            activity: self.clause_act_inc,
+            used: true,
+        });
+        cref
+    }
 
+    /// Like `derive_clause`, but for a clause that is only RAT on its first
+    /// literal. `rat_hints` pairs every clause containing the negation of that
+    /// literal with the hints deriving its resolvent with the new clause.
+    pub fn derive_rat_clause(
+        &mut self,
+        literals: Vec<PackedLit>,
+        rat_hints: Vec<(ClauseId, Vec<ClauseId>)>,
+    ) -> ClauseRef {
+        let cref = self.alloc_derived(literals, vec![], rat_hints);
+        self.original.push(cref);
+        cref
+    }
+
+    /// Replace a clause with a copy that lacks `lit`, derived through the
+    /// antecedents in `hints`. Same restrictions as `derive_clause`.
+    pub fn strengthen_clause(
+        &mut self,
+        cref: ClauseRef,
+        lit: PackedLit,
+        hints: Vec<ClauseId>,
+    ) -> ClauseRef {
+        let literals = self
+            .clauses
+            .literals(cref)
+            .iter()
+            .copied()
+            .filter(|&other| other != lit)
+            .collect_vec();
+        let strengthened = self.derive_subclause(cref, literals, hints);
+        self.remove_clause(cref);
+        strengthened
+    }
+
+    /// Derive some of the literals of a clause as a new clause, through the
+    /// antecedents in `hints`. The new clause takes the place of the old one
+    /// among either the original or the learned clauses, but the old one is
+    /// only gone once passed to `remove_clause`. Same restrictions as
+    /// `derive_clause`.
+    pub fn derive_subclause(
+        &mut self,
+        cref: ClauseRef,
+        literals: Vec<PackedLit>,
+        hints: Vec<ClauseId>,
+    ) -> ClauseRef {
+        let len = literals.len() as u32;
+        let subclause = self.alloc_derived(literals, hints, vec![]);
+        match self.learned.iter_mut().find(|learned| learned.cref == cref) {
+            Some(learned) => {
+                learned.cref = subclause;
+                learned.lbd = learned.lbd.min(len);
+            }
+            None => self.original.push(subclause),
+        }
+        subclause
+    }
+
+    fn alloc_derived(
+        &mut self,
+        literals: Vec<PackedLit>,
+        hints: Vec<ClauseId>,
+        rat_hints: Vec<(ClauseId, Vec<ClauseId>)>,
+    ) -> ClauseRef {
+        debug_assert_eq!(self.decision_level, 0);
+        let id = self.next_clause_id;
+        self.next_clause_id += 1;
+        let cref = self.clauses.alloc(id, &literals);
+        if let Some(proof) = &mut self.proof {
+            let clause = Clause {
+                literals: literals.into_iter().map(Lit::from).collect(),
+            };
+            proof.push(ProofStep::Add {
+                id,
+                clause,
+                hints,
+                rat_hints,
+            });
+        }
+        cref
+    }
+
+    /// Remove a clause from the formula. It must not be the reason for any
+    /// literal on the trail.
+    pub fn remove_clause(&mut self, cref: ClauseRef) {
+        debug_assert!(self.trail.iter().all(|e| e.reason.cref() != Some(cref)));
+        if let Some(proof) = &mut self.proof {
+            proof.push(ProofStep::Delete {
+                id: self.clauses.id(cref),
+                clause: self.clauses.clause(cref),
+            });
+        }
+        self.clauses.free(cref);
+    }
+
+    /// Drop the clauses removed since the last call and rebuild the watches,
+    /// so that propagation sees every derived clause.
+    pub fn finish_simplification(&mut self) {
+        let clauses = &self.clauses;
+        self.original.retain(|&cref| !clauses.is_freed(cref));
+        self.learned
+            .retain(|learned| !clauses.is_freed(learned.cref));
+        // Subclauses took the place of the clauses they were derived from.
+        self.learned.sort_unstable_by_key(|learned| learned.cref);
+        if self.clauses.wasted() > 0 {
+            self.collect_garbage();
+        }
         self.rebuild_watches();
     }
 
+    // Compact the clause arena and update every reference held outside it.
+    // The watch list must be rebuilt afterwards.
+    fn collect_garbage(&mut self) {
+        let relocation = self.clauses.collect_garbage();
+        let relocate = |cref| relocation.get(cref).expect("live clause was freed");
+        for cref in &mut self.original {
+            *cref = relocate(*cref);
+        }
+        for learned in &mut self.learned {
+            learned.cref = relocate(learned.cref);
+        }
+        for trail_element in &mut self.trail {
+            match &mut trail_element.reason {
+                TrailReason::Decision => {}
+                TrailReason::UnitProp(cref) | TrailReason::Binary { cref, .. } => {
+                    *cref = relocate(*cref)
+                }
+            }
+        }
+    }
+
     fn rebuild_watches(&mut self) {
-        self.watch_list = WatchList::new(self.formula.num_vars);
-        for (idx, clause) in self.formula.clauses.iter().enumerate() {
-            self.watch_list.add_clause(idx, clause);
-        }
-        // Replay trail assignments so the watch list reflects the current state.
-        // After a restart the trail is always empty, but we replay anyway so
-        // this method is correct if ever called mid-search.
-        let lits: Vec<Lit> = self.trail.iter().map(|e| e.lit).collect();
-        for lit in lits {
-            self.watch_list
-                .update_for_assignment(lit, &self.assignment, &self.formula.clauses);
+        self.watch_list = WatchList::new(self.num_vars);
+        for cref in self.clause_refs().collect_vec() {
+            let literals = self.clauses.literals(cref);
+            self.watch_list.add_clause(cref, literals);
+            // Unit clauses are never visited by propagation until they are
+            // falsified, so their literal is enqueued up front.
+            if let [lit] = *literals {
+                if self.assignment.value(lit).is_none() {
+                    self.assign_unitprop(lit, cref);
+                }
+            }
         }
+        // Propagate the whole trail again so the new watches catch up with
+        // literals that are already assigned.
+        self.qhead = 0;
     }
 
-    pub fn restart(&mut self) {
-        self.backjump_to_decision_level(0);
+    /// Backjump to level 0 to start the search over. With trail reuse, the
+    /// levels whose decision variables the branching heuristic still ranks
+    /// above the one it would decide on next are kept, as deciding again
+    /// would only rebuild them, unless the caller is about to `simplify` the
+    /// formula at level 0.
+    pub fn restart(&mut self, simplify: bool) {
+        let level = if self.reuse_trail && !simplify {
+            self.reusable_levels()
+        } else {
+            0
+        };
+        self.backjump_to_decision_level(level);
+        self.stats.reused_levels += level as u64;
+        self.target_len = 0;
+    }
+
+    fn reusable_levels(&mut self) -> u32 {
+        let Some(next) = self.next_decision_var() else {
+            return self.decision_level;
+        };
+        self.trail_lim
+            .iter()
+            .take_while(|&&pos| {
+                let decision = self.trail[pos].lit.var_index();
+                self.branching.prefers(decision, next.index - 1)
+            })
+            .count() as u32
     }
 
     #[cfg(test)]
     pub fn get_equivalent_clauses(&self) -> Vec<Clause> {
-        self.formula
+        self.formula()
             .clauses
             .iter()
             .filter_map(|solver_clause| solver_clause.get_equivalent_clause(&self.assignment))
@@ -461,151 +1001,200 @@ impl SolverState {
             .collect_vec()
     }
 
-    pub fn get_status(&self) -> Status {
-        // Return cached status if available (set when watches are updated)
-        if let Some(status) = self.watch_list.get_cached_status() {
-            return status.clone();
-        }
-
-        #[derive(Debug)]
-        enum ClauseResult {
-            Falsified(Clause),
-            Unit(Lit, Clause),
-            Unassigned(Lit),
-            Satisfied,
-        }
+    /// Unit propagate every trail literal not yet propagated, in the manner of
+    /// MiniSat's `propagate()`. Returns a falsified clause if one is found.
+    ///
+    /// The literal whose propagation found the conflict stays queued, so
+    /// calling this again reports a conflict until the solver backjumps.
+    pub fn propagate(&mut self) -> Option<ClauseRef> {
+        let start = self.qhead;
+        let conflict = self.propagate_queue();
+        let literals = self.trail[start..].iter().map(|element| element.lit);
+        self.branching
+            .propagated(literals, conflict.is_some(), self.conflict_count);
+        conflict
+    }
 
-        let results: Vec<_> = self
-            .formula
-            .clauses
-            .iter()
-            .map(|clause| {
-                let mut unassigned_in_clause = None;
-                let mut unassigned_count = 0;
-
-                for lit in clause.literals.iter() {
-                    match self.assignment.get(lit) {
-                        Some(false) => continue,
-                        Some(true) => return ClauseResult::Satisfied,
-                        None => {
-                            unassigned_in_clause = Some(*lit);
-                            unassigned_count += 1;
-                        }
+    fn propagate_queue(&mut self) -> Option<ClauseRef> {
+        let mut implied = Vec::new();
+        while self.qhead < self.trail.len() {
+            let lit = self.trail[self.qhead].lit;
+            for i in 0..self.watch_list.implications(lit).len() {
+                let Implication { lit: implied, cref } = self.watch_list.implications(lit)[i];
+                match self.assignment.value(implied) {
+                    Some(true) => {}
+                    Some(false) => return Some(cref),
+                    None => {
+                        let level = self.implied_level(implied, cref);
+                        self.enqueue(
+                            implied,
+                            TrailReason::Binary {
+                                other: lit.not(),
+                                cref,
+                            },
+                            level,
+                        )
                     }
                 }
-
-                match (unassigned_in_clause, unassigned_count) {
-                    (None, 0) => ClauseResult::Falsified(clause.clone()),
-                    (Some(lit), 1) => ClauseResult::Unit(lit, clause.clone()),
-                    (Some(lit), _) => ClauseResult::Unassigned(lit),
-                    _ => unreachable!(),
+            }
+            let conflict = self.watch_list.propagate(
+                lit.not(),
+                &self.assignment,
+                &mut self.clauses,
+                &mut implied,
+            );
+            for (lit, cref) in implied.drain(..) {
+                match self.assignment.value(lit) {
+                    Some(true) => {}
+                    // Implied both ways within this batch: the clause is now falsified.
+                    Some(false) => return Some(cref),
+                    None => self.assign_unitprop(lit, cref),
                 }
-            })
-            .collect();
-
-        // Process results: falsified first, then unit, then any unassigned
-        let mut unit = None;
-        let mut unassigned = None;
-        for result in results {
-            match result {
-                ClauseResult::Satisfied => continue,
-                ClauseResult::Falsified(clause) => return Status::Falsified(clause),
-
-                // Importantly, we return the _left-most_ unassigned literal
-                // (while prioritizing unit clauses), which plays well with early-returning
-                // the left-most falsified clause.
-                ClauseResult::Unit(lit, clause) => unit = unit.or(Some((lit, clause))),
-                ClauseResult::Unassigned(lit) => unassigned = unassigned.or(Some(lit)),
             }
+            if conflict.is_some() {
+                return conflict;
+            }
+            self.qhead += 1;
         }
+        None
+    }
 
-        if let Some((lit, clause)) = unit {
-            Status::UnassignedUnit(lit, clause)
-        } else if unassigned.is_some() {
-            Status::UnassignedDecision(self.next_decision_var().unwrap())
-        } else {
-            Status::Satisfied
+    pub fn get_status(&mut self) -> Status {
+        if let Some(cref) = self.propagate() {
+            return Status::Falsified(cref);
+        }
+        // Every assigned variable leaves the heap, so an empty heap means a
+        // complete assignment that propagation found no conflict in.
+        match self.next_decision_var() {
+            Some(var) => Status::UnassignedDecision(var),
+            None => Status::Satisfied,
         }
     }
 
     pub fn decide(&mut self, var: Var, value: Val) {
         self.decision_level += 1;
-        self.phase[var.index - 1] = value;
-        let heap = &mut self.var_heap;
-        let activity = &self.activity;
-        heap.remove(var.index - 1, activity);
-        // TODO: repeatedly cloning the assignment for this reason is inefficient
+        let lit = PackedLit::new(var.index - 1, value);
+        self.phase[lit.var_index()] = value;
+        self.branching.assigned(lit.var_index());
+        self.trail_lim.push(self.trail.len());
         self.trail.push(TrailElement {
-            lit: Lit { var, value },
-            reason: TrailReason::Decision(self.assignment.clone()),
+            lit,
+            reason: TrailReason::Decision,
         });
-        self.assignment.set(var, value, self.decision_level);
-        self.watch_list.update_for_assignment(
-            Lit { var, value },
-            &self.assignment,
-            &self.formula.clauses,
-        );
+        self.assignment.assign(lit, self.decision_level);
     }
 
-    pub fn assign_unitprop(&mut self, var: Var, value: Val, clause: Clause) {
-        self.phase[var.index - 1] = value;
-        let heap = &mut self.var_heap;
-        let activity = &self.activity;
-        heap.remove(var.index - 1, activity);
-        self.trail.push(TrailElement {
-            lit: Lit { var, value },
-            reason: TrailReason::UnitProp(clause),
-        });
-        self.assignment.set(var, value, self.decision_level);
-        self.watch_list.update_for_assignment(
-            Lit { var, value },
-            &self.assignment,
-            &self.formula.clauses,
-        );
+    pub fn assign_unitprop(&mut self, lit: PackedLit, cref: ClauseRef) {
+        let level = self.implied_level(lit, cref);
+        self.enqueue(lit, TrailReason::UnitProp(cref), level);
     }
 
-    pub fn learn_clause(&mut self, clause: Clause) {
-        let clause_idx = self.formula.clauses.len();
-        self.formula.clauses.push(clause.clone());
-        self.watch_list.add_clause(clause_idx, &clause);
+    fn enqueue(&mut self, lit: PackedLit, reason: TrailReason, level: u32) {
+        self.phase[lit.var_index()] = lit.value();
+        self.branching.assigned(lit.var_index());
+        self.trail.push(TrailElement { lit, reason });
+        self.assignment.assign(lit, level);
     }
 
-    pub fn backjump_to_decision_level(&mut self, decision_level: u32) {
-        let (cut_idx, snapshot) = self
-            .trail
+    // The level at which `cref` implies `lit`. With chronological
+    // backtracking, that is the highest level of its other literals, which
+    // may be below the current one.
+    fn implied_level(&self, lit: PackedLit, cref: ClauseRef) -> u32 {
+        if !self.chronological {
+            return self.decision_level;
+        }
+        self.clauses
+            .literals(cref)
             .iter()
-            .enumerate()
-            .filter_map(|(idx, elem)| match &elem.reason {
-                TrailReason::Decision(snapshot) => Some((idx, snapshot.clone())),
-                _ => None,
-            })
-            .nth(decision_level as usize)
-            .unwrap_or((0, Assignment::empty(self.formula.num_vars)));
+            .filter(|&&other| other != lit)
+            .map(|&other| self.assignment.level(other).unwrap())
+            .max()
+            .unwrap_or(0)
+    }
 
-        let unassigned: Vec<usize> = self.trail[cut_idx..]
+    /// The highest level among the literals of the falsified clause `cref`,
+    /// and how many of them are at it. With chronological backtracking this
+    /// may be below the current level, so the clause is also made to watch
+    /// two literals at the highest levels: backtracking below the conflict
+    /// then leaves neither of them false.
+    pub fn conflict_level(&mut self, cref: ClauseRef) -> (u32, usize) {
+        let level = |lit: PackedLit| self.assignment.level(lit).unwrap();
+        let literals = self.clauses.literals(cref);
+        let conflict_level = literals.iter().map(|&lit| level(lit)).max().unwrap();
+        let count = literals
             .iter()
-            .map(|e| e.lit.var.index - 1)
-            .collect();
-        self.trail.truncate(cut_idx);
-        self.decision_level = decision_level;
-        self.assignment = snapshot;
-        self.watch_list.clear_status(); // TODO: snapshot watch_list instead of needing to clear it?
-        for var_idx in unassigned {
-            let heap = &mut self.var_heap;
-            let activity = &self.activity;
-            heap.insert(var_idx, activity);
+            .filter(|&&lit| level(lit) == conflict_level)
+            .count();
+        // Unit and binary clauses watch all of their literals.
+        if self.chronological && literals.len() > 2 {
+            let mut sorted = literals.to_vec();
+            sorted.sort_by_key(|&lit| Reverse(level(lit)));
+            self.watch_list
+                .rewatch(cref, [literals[0], literals[1]], [sorted[0], sorted[1]]);
+            self.clauses.literals_mut(cref).copy_from_slice(&sorted);
         }
+        (conflict_level, count)
+    }
+
+    // Learned clauses only become candidates for deletion through `learn_clause_with_meta`.
+    fn learn_clause(&mut self, mut literals: Vec<PackedLit>, hints: Vec<ClauseId>) -> ClauseRef {
+        let id = self.next_clause_id;
+        self.next_clause_id += 1;
+        if let Some(proof) = &mut self.proof {
+            let clause = Clause {
+                literals: literals.iter().map(|&lit| lit.into()).collect(),
+            };
+            proof.push(ProofStep::Add {
+                id,
+                clause,
+                hints,
+                rat_hints: vec![],
+            });
+        }
+        // Watch the asserting literal and the one that is unassigned last on
+        // backjumping, so the watches are valid at the backjump level.
+        literals.sort_by_key(|&lit| Reverse(self.assignment.level(lit)));
+        let cref = self.clauses.alloc(id, &literals);
+        self.watch_list.add_clause(cref, &literals);
+        cref
+    }
+
+    pub fn backjump_to_decision_level(&mut self, decision_level: u32) {
+        let Some(&cut_idx) = self.trail_lim.get(decision_level as usize) else {
+            return;
+        };
+        // After chronological backtracking, literals past the cut may belong
+        // to levels that stay. They keep their order and are propagated again.
+        let mut kept = vec![];
+        for elem in self.trail.drain(cut_idx..) {
+            if self.assignment.level(elem.lit).unwrap() <= decision_level {
+                kept.push(elem);
+            } else {
+                self.assignment.unassign(elem.lit);
+                self.branching.unassigned(elem.lit.var_index());
+            }
+        }
+        self.trail.extend(kept);
+        self.trail_lim.truncate(decision_level as usize);
+        self.decision_level = decision_level;
+        self.qhead = self.qhead.min(cut_idx);
     }
 
     pub fn pure_literal_eliminate(&mut self) {
-        let mut seen_positive = vec![false; self.formula.num_vars];
-        let mut seen_negative = vec![false; self.formula.num_vars];
-        for clause in &self.formula.clauses {
-            for lit in &clause.literals {
-                if lit.value == Val::True {
-                    seen_positive[lit.var.index - 1] = true;
+        // Level-0 consequences must be in place before any decision. A
+        // conflict stays queued for the caller to find.
+        if self.propagate().is_some() {
+            return;
+        }
+
+        let mut seen_positive = vec![false; self.num_vars];
+        let mut seen_negative = vec![false; self.num_vars];
+        for cref in self.clause_refs() {
+            for lit in self.clauses.literals(cref) {
+                if lit.value() == Val::True {
+                    seen_positive[lit.var_index()] = true;
                 } else {
-                    seen_negative[lit.var.index - 1] = true;
+                    seen_negative[lit.var_index()] = true;
                 }
             }
         }
@@ -643,7 +1232,7 @@ pub fn check_assignment(cnf: &CnfFormula, assignment: &Assignment) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::DeletionStrategy;
+    use crate::config::{DeletionStrategy, SolverConfig};
     use crate::parser::parse_dimacs_str;
 
     // Directly write an assignment without touching the trail or watch list.
@@ -660,6 +1249,10 @@ mod tests {
         }
     }
 
+    fn packed_lit(index: usize, value: Val) -> PackedLit {
+        lit(index, value).into()
+    }
+
     #[test]
     fn test_lbd_single_level() {
         // All literals from the same decision level → LBD = 1
@@ -668,9 +1261,11 @@ mod tests {
         force_assign(&mut state, Var { index: 1 }, Val::True, 1);
         force_assign(&mut state, Var { index: 2 }, Val::True, 1);
         force_assign(&mut state, Var { index: 3 }, Val::False, 1);
-        let clause = Clause {
-            literals: vec![lit(1, Val::False), lit(2, Val::False), lit(3, Val::True)],
-        };
+        let clause = [
+            packed_lit(1, Val::False),
+            packed_lit(2, Val::False),
+            packed_lit(3, Val::True),
+        ];
         assert_eq!(state.compute_lbd(&clause), 1);
     }
 
@@ -682,9 +1277,11 @@ mod tests {
         force_assign(&mut state, Var { index: 1 }, Val::True, 1);
         force_assign(&mut state, Var { index: 2 }, Val::True, 2);
         force_assign(&mut state, Var { index: 3 }, Val::False, 3);
-        let clause = Clause {
-            literals: vec![lit(1, Val::False), lit(2, Val::False), lit(3, Val::True)],
-        };
+        let clause = [
+            packed_lit(1, Val::False),
+            packed_lit(2, Val::False),
+            packed_lit(3, Val::True),
+        ];
         assert_eq!(state.compute_lbd(&clause), 3);
     }
 
@@ -699,28 +1296,40 @@ mod tests {
         force_assign(&mut state, Var { index: 3 }, Val::True, 3);
 
         // LBD=1: only var 1 (level 1)
-        state.learn_clause_with_meta(Clause {
-            literals: vec![lit(1, Val::False)],
-        });
+        state.learn_clause_with_meta(vec![packed_lit(1, Val::False)], vec![]);
         // LBD=2: vars 1 (level 1) and 2 (level 2)
-        state.learn_clause_with_meta(Clause {
-            literals: vec![lit(1, Val::False), lit(2, Val::False)],
-        });
+        state.learn_clause_with_meta(
+            vec![packed_lit(1, Val::False), packed_lit(2, Val::False)],
+            vec![],
+        );
         // LBD=3: vars 1, 2, 3 at levels 1, 2, 3
-        state.learn_clause_with_meta(Clause {
-            literals: vec![lit(1, Val::False), lit(2, Val::False), lit(3, Val::False)],
-        });
+        state.learn_clause_with_meta(
+            vec![
+                packed_lit(1, Val::False),
+                packed_lit(2, Val::False),
+                packed_lit(3, Val::False),
+            ],
+            vec![],
+        );
 
-        assert_eq!(state.clause_meta.len(), 3);
+        assert_eq!(state.learned.len(), 3);
         state.delete_weak_learned_clauses(&DeletionStrategy::Lbd { max_lbd: 2 });
 
         // Only the two clauses with LBD ≤ 2 survive
-        assert_eq!(state.clause_meta.len(), 2);
-        assert!(state.clause_meta.iter().all(|m| m.lbd <= 2));
+        assert_eq!(state.learned.len(), 2);
+        assert!(state.learned.iter().all(|l| l.lbd <= 2));
         assert_eq!(
-            state.formula.clauses.len() - state.learned_from,
+            state.formula().clauses.len(),
             2,
-            "formula.clauses and clause_meta must stay in sync"
+            "deleted clauses must leave the clause database"
+        );
+        // The arena is compacted and surviving references still resolve.
+        assert_eq!(state.clauses.wasted(), 0);
+        let mut survivor = state.clauses.literals(state.learned[1].cref).to_vec();
+        survivor.sort();
+        assert_eq!(
+            survivor,
+            [packed_lit(1, Val::False), packed_lit(2, Val::False)]
         );
     }
 
@@ -735,12 +1344,13 @@ mod tests {
 
         // Each clause gets a strictly higher activity than the previous.
         for _ in 0..4 {
-            state.learn_clause_with_meta(Clause {
-                literals: vec![lit(1, Val::False), lit(2, Val::False)],
-            });
+            state.learn_clause_with_meta(
+                vec![packed_lit(1, Val::False), packed_lit(2, Val::False)],
+                vec![],
+            );
         }
 
-        let activities_before: Vec<f64> = state.clause_meta.iter().map(|m| m.activity).collect();
+        let activities_before: Vec<f64> = state.learned.iter().map(|l| l.activity).collect();
         assert!(
             activities_before.windows(2).all(|w| w[0] < w[1]),
             "activities should be strictly increasing"
@@ -748,13 +1358,13 @@ mod tests {
 
         // Delete the lowest-activity 50% → keep the 2 newest
         state.delete_weak_learned_clauses(&DeletionStrategy::Activity { fraction: 0.5 });
-        assert_eq!(state.clause_meta.len(), 2);
+        assert_eq!(state.learned.len(), 2);
 
         // Every surviving clause must have higher activity than every deleted one
         let min_surviving = state
-            .clause_meta
+            .learned
             .iter()
-            .map(|m| m.activity)
+            .map(|l| l.activity)
             .fold(f64::MAX, f64::min);
         let max_deleted = activities_before[..2]
             .iter()
@@ -763,6 +1373,214 @@ mod tests {
         assert!(min_surviving > max_deleted);
     }
 
+    #[test]
+    fn test_deletion_tiered_keeps_core_and_used_tier2() {
+        let cnf = parse_dimacs_str(b"p cnf 4 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.seal_original_clauses();
+        for index in 1..=4 {
+            force_assign(&mut state, Var { index }, Val::True, index as u32);
+        }
+        let negated = |indices: &[usize]| {
+            indices
+                .iter()
+                .map(|&index| packed_lit(index, Val::False))
+                .collect_vec()
+        };
+        for indices in [&[1, 2][..], &[1, 2, 3], &[2, 3, 4], &[1, 2, 3, 4]] {
+            state.learn_clause_with_meta(negated(indices), vec![]);
+        }
+        let survivors = |state: &SolverState| {
+            state
+                .learned
+                .iter()
+                .map(|l| {
+                    state
+                        .clauses
+                        .literals(l.cref)
+                        .iter()
+                        .copied()
+                        .sorted()
+                        .collect_vec()
+                })
+                .collect_vec()
+        };
+        let strategy = DeletionStrategy::Tiered {
+            core_lbd: 2,
+            tier2_lbd: 3,
+            fraction: 1.0,
+        };
+
+        // Fresh clauses count as used, so only the local one goes.
+        state.delete_weak_learned_clauses(&strategy);
+        assert_eq!(
+            survivors(&state),
+            [negated(&[1, 2]), negated(&[1, 2, 3]), negated(&[2, 3, 4])]
+        );
+
+        // With x3 and x4 now at level 1, the last clause spans two levels
+        // and joins the core once used, while the tier-2 clause that was not
+        // used drops out.
+        force_assign(&mut state, Var { index: 3 }, Val::True, 1);
+        force_assign(&mut state, Var { index: 4 }, Val::True, 1);
+        let used = [state.learned[0].cref, state.learned[2].cref];
+        state.bump_clause_activity(&used);
+        assert_eq!(state.learned[2].lbd, 2);
+        state.delete_weak_learned_clauses(&strategy);
+        assert_eq!(survivors(&state), [negated(&[1, 2]), negated(&[2, 3, 4])]);
+    }
+
+    #[test]
+    fn test_target_phases_follow_longest_conflict_free_trail() {
+        let cnf = parse_dimacs_str(b"p cnf 3 1\n-1 2 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.decide(Var { index: 1 }, Val::True);
+        assert!(state.propagate().is_none());
+        state.decide(Var { index: 3 }, Val::True);
+        // Only the first level is known to be conflict-free.
+        state.update_target_phases();
+        assert_eq!(state.target_len, 2);
+        assert_eq!(state.get_target_phase(Var { index: 2 }), Val::True);
+        assert_eq!(state.get_target_phase(Var { index: 3 }), Val::False);
+
+        // A restart forgets the target, but not the best phases.
+        state.restart(false);
+        state.decide(Var { index: 3 }, Val::True);
+        state.update_target_phases();
+        assert_eq!(state.target_len, 0);
+        assert_eq!(state.take_best_phases(), [Val::True, Val::True, Val::False]);
+        assert_eq!(state.best_len, 0);
+    }
+
+    #[test]
+    fn test_add_clause_is_permanent() {
+        let cnf = parse_dimacs_str(b"p cnf 3 1\n1 2 3 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.seal_original_clauses();
+
+        state.decide(Var { index: 1 }, Val::True);
+        state.decide(Var { index: 2 }, Val::True);
+        state.learn_clause_with_meta(
+            vec![packed_lit(1, Val::False), packed_lit(2, Val::False)],
+            vec![],
+        );
+
+        let added = Clause {
+            literals: vec![lit(3, Val::False), lit(2, Val::True)],
+        };
+        assert!(state.add_clause(added));
+        assert_eq!(state.decision_level, 0);
+        assert_eq!(state.original.len(), 2);
+        assert_eq!(state.learned.len(), 1);
+        // Stored sorted, in the original section ahead of the learned clause.
+        assert_eq!(
+            state.formula().clauses[1].literals,
+            vec![lit(2, Val::True), lit(3, Val::False)]
+        );
+
+        // Deleting every learned clause must leave the added clause in place.
+        state.delete_weak_learned_clauses(&DeletionStrategy::Lbd { max_lbd: 0 });
+        assert_eq!(state.formula().clauses.len(), 2);
+        assert!(state.learned.is_empty());
+    }
+
+    #[test]
+    fn test_add_clause_tautology_and_empty() {
+        let cnf = parse_dimacs_str(b"p cnf 2 1\n1 2 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.seal_original_clauses();
+        assert!(state.add_clause(Clause {
+            literals: vec![lit(1, Val::True), lit(2, Val::False), lit(1, Val::False)],
+        }));
+        assert_eq!(state.formula().clauses.len(), 1);
+        assert!(!state.add_clause(Clause { literals: vec![] }));
+    }
+
+    #[test]
+    fn test_new_var_is_decidable() {
+        let cnf = parse_dimacs_str(b"p cnf 1 1\n1 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        let var = state.new_var();
+        assert_eq!(var, Var { index: 2 });
+        assert_eq!(state.num_vars, 2);
+        assert_eq!(state.assignment.num_vars(), 2);
+        assert!(state.add_clause(Clause {
+            literals: vec![lit(1, Val::False), lit(2, Val::True)],
+        }));
+        state.bump_var_activity(&[packed_lit(2, Val::True)]);
+        assert_eq!(state.next_decision_var(), Some(var));
+
+        assert_eq!(state.get_status(), Status::Satisfied);
+        assert_eq!(state.assignment.get(&lit(2, Val::True)), Some(true));
+    }
+
+    #[test]
+    fn test_chronological_backjump_keeps_lower_levels() {
+        let cnf = parse_dimacs_str(b"p cnf 3 1\n-1 3 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.configure_search(&SolverConfig {
+            chronological_backtracking: true,
+            ..SolverConfig::default()
+        });
+        state.decide(Var { index: 1 }, Val::True);
+        state.decide(Var { index: 2 }, Val::True);
+        assert_eq!(state.propagate(), None);
+        // 3 follows from the decision at level 1 alone, but is on the trail
+        // after the one at level 2.
+        assert_eq!(state.assignment.level(packed_lit(3, Val::True)), Some(1));
+
+        state.backjump_to_decision_level(1);
+        let trail = state.trail.iter().map(|element| element.lit).collect_vec();
+        assert_eq!(
+            trail,
+            vec![packed_lit(1, Val::True), packed_lit(3, Val::True)]
+        );
+        assert_eq!(state.assignment.get(&lit(2, Val::True)), None);
+    }
+
+    #[test]
+    fn test_restart_reuses_trail() {
+        let cnf = parse_dimacs_str(b"p cnf 4 1\n1 2 3 4 0\n").unwrap();
+        let mut state = SolverState::from_cnf(&cnf);
+        state.configure_search(&SolverConfig {
+            reuse_trail: true,
+            ..SolverConfig::default()
+        });
+        state.bump_var_activity(&[packed_lit(1, Val::True), packed_lit(3, Val::True)]);
+        state.bump_var_activity(&[packed_lit(1, Val::True)]);
+        state.decide(Var { index: 1 }, Val::False);
+        state.decide(Var { index: 2 }, Val::False);
+        // 3 would be decided next: it ranks below 1 but above 2.
+        let mut simplifying = state.clone();
+        state.restart(false);
+        assert_eq!(state.decision_level, 1);
+        assert_eq!(state.trail.len(), 1);
+        assert_eq!(state.stats.reused_levels, 1);
+        simplifying.restart(true);
+        assert_eq!(simplifying.decision_level, 0);
+        assert_eq!(simplifying.stats.reused_levels, 0);
+    }
+
+    #[test]
+    fn test_backjump_pops_trail() {
+        let mut state =
+            SolverState::from_cnf(&parse_dimacs_str(b"\np cnf 4 3\n1 0\n-2 3 0\n-3 4 0").unwrap());
+        assert_eq!(state.propagate(), None);
+        state.decide(Var { index: 2 }, Val::True);
+        assert_eq!(state.propagate(), None);
+        assert_eq!(state.trail.len(), 4);
+
+        state.backjump_to_decision_level(0);
+        assert_eq!(state.decision_level, 0);
+        assert_eq!(state.trail.len(), 1);
+        assert_eq!(state.assignment.get(&lit(1, Val::True)), Some(true));
+        for index in 2..=4 {
+            assert_eq!(state.assignment.get(&lit(index, Val::True)), None);
+        }
+        // Unassigned variables are decidable again.
+        assert!(state.next_decision_var().is_some_and(|v| v.index != 1));
+    }
+
     #[test]
     fn test_ucp() {
         let mut ucp = SolverState::from_cnf(
@@ -770,31 +1588,42 @@ mod tests {
         );
         let expected = parse_dimacs_str(b"\np cnf 5 1\n3 4 0").unwrap();
 
-        while let Status::UnassignedUnit(lit, clause) = ucp.get_status() {
-            ucp.assign_unitprop(lit.var, lit.value, clause);
-        }
+        assert_eq!(ucp.propagate(), None);
 
+        let reasons: Vec<(Lit, ClauseId, Clause)> = ucp
+            .trail
+            .iter()
+            .map(|e| {
+                let cref = e.reason.cref().expect("no decisions were made");
+                // propagation may have reordered the watched literals
+                let mut clause = ucp.clauses.clause(cref);
+                clause.literals.sort();
+                (e.lit.into(), ucp.clauses.id(cref), clause)
+            })
+            .collect();
         assert_eq!(
-            ucp.trail,
+            reasons,
             [
-                TrailElement {
-                    lit: Lit {
+                (
+                    Lit {
                         var: Var { index: 1 },
                         value: Val::True
                     },
-                    reason: TrailReason::UnitProp(Clause {
+                    3,
+                    Clause {
                         literals: vec![Lit {
                             var: Var { index: 1 },
                             value: Val::True
                         }]
-                    })
-                },
-                TrailElement {
-                    lit: Lit {
+                    }
+                ),
+                (
+                    Lit {
                         var: Var { index: 2 },
                         value: Val::False
                     },
-                    reason: TrailReason::UnitProp(Clause {
+                    2,
+                    Clause {
                         literals: vec![
                             Lit {
                                 var: Var { index: 1 },
@@ -805,21 +1634,83 @@ mod tests {
                                 value: Val::False
                             }
                         ]
-                    })
-                }
+                    }
+                )
             ]
         );
 
         assert_eq!(ucp.get_equivalent_clauses(), expected.clauses);
     }
 
+    #[test]
+    fn test_binary_implications() {
+        let mut state = SolverState::from_cnf(
+            &parse_dimacs_str(b"\np cnf 4 3\n-1 2 0\n-1 3 0\n-2 -3 4 0").unwrap(),
+        );
+        let implied = |state: &SolverState, lit| {
+            state
+                .binary_implications(lit)
+                .iter()
+                .map(|implication| implication.lit)
+                .collect_vec()
+        };
+        assert_eq!(
+            implied(&state, packed_lit(1, Val::True)),
+            [packed_lit(2, Val::True), packed_lit(3, Val::True)]
+        );
+        assert_eq!(
+            implied(&state, packed_lit(2, Val::False)),
+            [packed_lit(1, Val::False)]
+        );
+        // Longer clauses are not part of the graph.
+        assert!(implied(&state, packed_lit(2, Val::True)).is_empty());
+
+        state.decide(Var { index: 1 }, Val::True);
+        assert_eq!(state.propagate(), None);
+        assert_eq!(state.trail.len(), 4);
+        assert_eq!(
+            state.trail[1].reason,
+            TrailReason::Binary {
+                other: packed_lit(1, Val::False),
+                cref: state.original[0],
+            }
+        );
+        assert_eq!(
+            state.reason_literals(&state.trail[1]).collect_vec(),
+            [packed_lit(1, Val::False)]
+        );
+        assert_eq!(
+            state.trail[3].reason,
+            TrailReason::UnitProp(state.original[2])
+        );
+    }
+
+    #[test]
+    fn test_conflict_stays_queued_until_backjump() {
+        let mut state =
+            SolverState::from_cnf(&parse_dimacs_str(b"\np cnf 3 2\n-1 2 0\n-1 -2 0").unwrap());
+        state.decide(Var { index: 1 }, Val::True);
+        let conflict = state.propagate();
+        assert!(conflict.is_some());
+        assert_eq!(state.propagate(), conflict);
+        assert_eq!(state.get_status(), Status::Falsified(conflict.unwrap()));
+
+        state.backjump_to_decision_level(0);
+        assert_eq!(state.propagate(), None);
+        assert!(matches!(state.get_status(), Status::UnassignedDecision(_)));
+    }
+
     #[test]
     fn test_ple() {
         let mut ple = SolverState::from_cnf(
             &parse_dimacs_str(b"\np cnf 5 5\n1 2 0\n1 -2 0\n3 4 0\n3 -4 0\n-3 0").unwrap(),
         );
-        let expected = parse_dimacs_str(b"\np cnf 5 3\n3 4 0\n3 -4 0\n-3 0").unwrap();
+        // The unit -3 is propagated as soon as the state is built, which
+        // falsifies 3 -4, so no pure literal is decided on top of the conflict.
+        let mut expected = parse_dimacs_str(b"\np cnf 5 2\n1 2 0\n1 -2 0").unwrap();
+        expected.clauses.push(Clause { literals: vec![] });
         ple.pure_literal_eliminate();
         assert_eq!(ple.get_equivalent_clauses(), expected.clauses);
+        assert!(ple.propagate().is_some());
     }
 }
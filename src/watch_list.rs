@@ -1,127 +1,176 @@
 use crate::formula::*;
 use crate::solver_state::*;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 struct Watch {
-    clause_idx: usize,
-    blocking_lit: Lit,
+    cref: ClauseRef,
+    blocking_lit: PackedLit,
+}
+
+/// An edge of the binary implication graph: `lit` is implied through the
+/// binary clause `cref`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Implication {
+    pub lit: PackedLit,
+    pub cref: ClauseRef,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct WatchList {
     watches: Vec<Vec<Watch>>,
-    cached_status: Option<Status>,
+    // Indexed by literal: what becomes true once it is true.
+    implications: Vec<Vec<Implication>>,
 }
 
-// Implementation of the 2 watched literal algorithm
+// Implementation of the 2 watched literal algorithm. The watched literals of
+// a clause are always its first two; propagation reorders them in place.
+// Binary clauses are kept apart as implication lists, which propagate
+// without looking at the clause itself.
 impl WatchList {
     pub fn new(num_vars: usize) -> Self {
         Self {
             watches: vec![vec![]; num_vars * 2],
-            cached_status: None,
+            implications: vec![vec![]; num_vars * 2],
         }
     }
 
-    fn to_watch_index(lit: Lit) -> usize {
-        let var_idx = lit.var.index - 1;
-        match lit.value {
-            Val::True => var_idx * 2,
-            Val::False => var_idx * 2 + 1,
-        }
+    pub fn add_var(&mut self) {
+        self.watches.extend([vec![], vec![]]);
+        self.implications.extend([vec![], vec![]]);
     }
 
-    pub fn add_clause(&mut self, clause_idx: usize, clause: &Clause) {
-        if clause.literals.is_empty() {
+    /// The literals implied by `lit` through a single binary clause.
+    pub fn implications(&self, lit: PackedLit) -> &[Implication] {
+        &self.implications[lit.index()]
+    }
+
+    pub fn add_clause(&mut self, cref: ClauseRef, literals: &[PackedLit]) {
+        if literals.is_empty() {
             unreachable!();
-        } else if clause.literals.len() == 1 {
-            let lit0 = clause.literals[0];
-            self.watches[Self::to_watch_index(lit0)].push(Watch {
-                clause_idx,
+        } else if literals.len() == 1 {
+            let lit0 = literals[0];
+            self.watches[lit0.index()].push(Watch {
+                cref,
                 blocking_lit: lit0, // blocked by itself, this is handled in the propagation logic
             });
+        } else if literals.len() == 2 {
+            let (lit0, lit1) = (literals[0], literals[1]);
+            self.implications[lit0.not().index()].push(Implication { lit: lit1, cref });
+            self.implications[lit1.not().index()].push(Implication { lit: lit0, cref });
         } else {
-            let lit0 = clause.literals[0];
-            let lit1 = clause.literals[1];
-            self.watches[Self::to_watch_index(lit0)].push(Watch {
-                clause_idx,
+            let lit0 = literals[0];
+            let lit1 = literals[1];
+            self.watches[lit0.index()].push(Watch {
+                cref,
                 blocking_lit: lit1,
             });
-            self.watches[Self::to_watch_index(lit1)].push(Watch {
-                clause_idx,
+            self.watches[lit1.index()].push(Watch {
+                cref,
                 blocking_lit: lit0,
             });
         }
     }
 
-    pub fn get_cached_status(&self) -> Option<&Status> {
-        self.cached_status.as_ref()
-    }
-
-    pub fn clear_status(&mut self) {
-        self.cached_status = None;
+    /// Move the watches of `cref` from `old` to `new`, after its literals were
+    /// reordered to put `new` first.
+    pub fn rewatch(&mut self, cref: ClauseRef, old: [PackedLit; 2], new: [PackedLit; 2]) {
+        for lit in old {
+            if !new.contains(&lit) {
+                let watches = &mut self.watches[lit.index()];
+                if let Some(pos) = watches.iter().position(|watch| watch.cref == cref) {
+                    watches.swap_remove(pos);
+                }
+            }
+        }
+        for (i, lit) in new.into_iter().enumerate() {
+            if !old.contains(&lit) {
+                self.watches[lit.index()].push(Watch {
+                    cref,
+                    blocking_lit: new[1 - i],
+                });
+            }
+        }
     }
 
-    pub fn update_for_assignment(
+    /// Visit every clause of three or more literals watching `false_lit`,
+    /// which has just become false.
+    /// Clauses that become unit push their remaining literal onto `implied`;
+    /// the literals are not assigned yet, so the caller must check them before
+    /// enqueueing. Returns a clause with every literal false, if one is found.
+    pub fn propagate(
         &mut self,
-        assigned_lit: Lit,
+        false_lit: PackedLit,
         assignment: &Assignment,
-        clauses: &[Clause],
-    ) {
-        // clear cached status to be re-assigned later
-        self.cached_status = None;
-
-        let neg_lit = assigned_lit.not();
+        clauses: &mut ClauseDb,
+        implied: &mut Vec<(PackedLit, ClauseRef)>,
+    ) -> Option<ClauseRef> {
+        let mut watches = std::mem::take(&mut self.watches[false_lit.index()]);
+        let mut conflict = None;
 
-        // look for a clause containing neg_lit
+        // Watches at 0..kept stay on false_lit; the rest of 0..i has moved elsewhere.
+        let mut kept = 0;
         let mut i = 0;
-        while i < self.watches[Self::to_watch_index(neg_lit)].len() {
-            let watch = self.watches[Self::to_watch_index(neg_lit)][i].clone();
+        while i < watches.len() {
+            let watch = watches[i];
+            i += 1;
 
-            if assignment.get(&watch.blocking_lit) == Some(true) {
+            if assignment.value(watch.blocking_lit) == Some(true) {
                 // this clause is satisfied, so we skip it
-                i += 1;
+                watches[kept] = watch;
+                kept += 1;
                 continue;
             }
 
-            let clause = &clauses[watch.clause_idx];
+            let literals = clauses.literals_mut(watch.cref);
+            if literals.len() == 1 {
+                watches[kept] = watch;
+                kept += 1;
+                conflict = Some(watch.cref);
+                break;
+            }
+
+            // Make sure the false literal is the second watch.
+            if literals[0] == false_lit {
+                literals.swap(0, 1);
+            }
+            let first = literals[0];
+            let watch = Watch {
+                cref: watch.cref,
+                blocking_lit: first,
+            };
+            if assignment.value(first) == Some(true) {
+                watches[kept] = watch;
+                kept += 1;
+                continue;
+            }
 
             // looking for a new literal to watch; can be either unassigned or assigned true
-            let mut found_new_watch = false;
-            let mut new_watch_lit = neg_lit;
-            for &lit in &clause.literals {
-                if lit != neg_lit
-                    && lit != watch.blocking_lit
-                    && assignment.get(&lit) != Some(false)
-                {
-                    new_watch_lit = lit;
-                    found_new_watch = true;
-                    break;
-                }
+            if let Some(k) =
+                (2..literals.len()).find(|&k| assignment.value(literals[k]) != Some(false))
+            {
+                literals.swap(1, k);
+                self.watches[literals[1].index()].push(watch);
+                continue;
             }
 
-            if found_new_watch {
-                let removed_watch = self.watches[Self::to_watch_index(neg_lit)].swap_remove(i);
-                self.watches[Self::to_watch_index(new_watch_lit)].push(Watch {
-                    clause_idx: removed_watch.clause_idx,
-                    blocking_lit: watch.blocking_lit,
-                });
-                // don't increment i, because of how swap_remove works
-            } else {
-                // couldn't find a new unassigned variable - clause is unit or falsified
-                // cache the status eagerly
-                if assignment.get(&watch.blocking_lit) == Some(false) {
-                    // falsified
-                    self.cached_status = Some(Status::Falsified(clause.clone()));
-                    return;
-                } else if assignment.get(&watch.blocking_lit).is_none() {
-                    // unit clause
-                    if self.cached_status.is_none() {
-                        self.cached_status =
-                            Some(Status::UnassignedUnit(watch.blocking_lit, clause.clone()));
-                    }
-                }
-                i += 1;
+            // couldn't find a new literal to watch - clause is unit or falsified
+            watches[kept] = watch;
+            kept += 1;
+            if assignment.value(first) == Some(false) {
+                conflict = Some(watch.cref);
+                break;
             }
+            implied.push((first, watch.cref));
+        }
+
+        // On a conflict, the unvisited watches stay where they are.
+        while i < watches.len() {
+            watches[kept] = watches[i];
+            kept += 1;
+            i += 1;
         }
+        watches.truncate(kept);
+        self.watches[false_lit.index()] = watches;
+        conflict
     }
 }